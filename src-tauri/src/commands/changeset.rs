@@ -0,0 +1,271 @@
+use tauri::State;
+use crate::state::AppState;
+use crate::commands::table::{
+    delete_statement, insert_statement, load_columns, rows_matched, update_statement, ColumnInfo,
+};
+use mysql_async::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Savepoint used instead of a transaction when the session already has one open
+const SAVEPOINT: &str = "omnimin_changeset";
+
+/// One grid change. Rows are addressed the same way as in `update_row` and `delete_rows`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChangeOperation {
+    Insert {
+        db: String,
+        table: String,
+        values: HashMap<String, serde_json::Value>,
+    },
+    Update {
+        db: String,
+        table: String,
+        key: HashMap<String, serde_json::Value>,
+        values: HashMap<String, serde_json::Value>,
+        /// Values the grid loaded; the update fails if the row no longer holds them
+        original: Option<HashMap<String, serde_json::Value>>,
+    },
+    Delete {
+        db: String,
+        table: String,
+        key: HashMap<String, serde_json::Value>,
+    },
+}
+
+impl ChangeOperation {
+    fn target(&self) -> (&str, &str) {
+        match self {
+            ChangeOperation::Insert { db, table, .. }
+            | ChangeOperation::Update { db, table, .. }
+            | ChangeOperation::Delete { db, table, .. } => (db, table),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct OperationResult {
+    pub index: usize,
+    pub status: String, // "planned" | "applied" | "failed" | "rolled_back" | "skipped"
+    /// Statement as prepared, with `?` placeholders
+    pub sql: Option<String>,
+    /// Parameter values as SQL literals, in placeholder order
+    pub params: Vec<String>,
+    pub affected_rows: u64,
+    pub last_insert_id: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChangesetResult {
+    pub dry_run: bool,
+    /// Whether the changes are in the database (or in the session's open transaction)
+    pub applied: bool,
+    pub operations: Vec<OperationResult>,
+    /// Set when undoing a failed batch went wrong. The server may already have rolled back
+    /// on its own (after a deadlock, for one), taking the session's open transaction with it.
+    pub rollback_error: Option<String>,
+}
+
+struct Planned {
+    sql: String,
+    params: Vec<mysql_async::Value>,
+}
+
+/// Applies a batch of grid changes atomically. With `dry_run` nothing is executed and the
+/// result lists the statements that would run. Otherwise every statement runs in one
+/// transaction (a savepoint if the session already has one open) that is rolled back on the
+/// first failure; changes to non-transactional tables such as MyISAM cannot be undone.
+#[tauri::command]
+pub async fn apply_changeset(
+    operations: Vec<ChangeOperation>,
+    dry_run: Option<bool>,
+    session_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ChangesetResult, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let mut conn = state.get_conn(session_id.as_deref()).await?;
+
+    // Build every statement up front so a bad value fails before anything runs
+    let mut columns: HashMap<(String, String), Vec<ColumnInfo>> = HashMap::new();
+    let mut plan: Vec<Result<Planned, String>> = Vec::new();
+    for op in &operations {
+        let (db, table) = op.target();
+        let target = (db.to_string(), table.to_string());
+        if !columns.contains_key(&target) {
+            let loaded = load_columns(&mut conn, db, table).await?;
+            columns.insert(target.clone(), loaded);
+        }
+        plan.push(plan_operation(op, &columns[&target]));
+    }
+
+    let mut results: Vec<OperationResult> = plan
+        .iter()
+        .enumerate()
+        .map(|(index, planned)| OperationResult {
+            index,
+            status: "planned".to_string(),
+            sql: planned.as_ref().ok().map(|p| p.sql.clone()),
+            params: planned
+                .as_ref()
+                .map(|p| p.params.iter().map(|v| v.as_sql(false)).collect())
+                .unwrap_or_default(),
+            affected_rows: 0,
+            last_insert_id: None,
+            error: planned.as_ref().err().cloned(),
+        })
+        .collect();
+
+    let invalid = results.iter().any(|r| r.error.is_some());
+    if dry_run || invalid {
+        if invalid {
+            for result in results.iter_mut() {
+                result.status = if result.error.is_some() { "failed" } else { "skipped" }.to_string();
+            }
+        }
+        return Ok(ChangesetResult { dry_run, applied: false, operations: results, rollback_error: None });
+    }
+
+    let pinned = conn.is_pinned();
+    let (begin, commit, rollback) = if pinned {
+        (format!("SAVEPOINT {}", SAVEPOINT), format!("RELEASE SAVEPOINT {}", SAVEPOINT), format!("ROLLBACK TO SAVEPOINT {}", SAVEPOINT))
+    } else {
+        ("START TRANSACTION".to_string(), "COMMIT".to_string(), "ROLLBACK".to_string())
+    };
+    conn.query_drop(begin).await.map_err(|e| e.to_string())?;
+
+    let mut failed = None;
+    for (i, planned) in plan.into_iter().enumerate() {
+        let planned = planned.expect("validated above");
+        let outcome = execute(&mut conn, &operations[i], planned).await;
+        let result = &mut results[i];
+        match outcome {
+            Ok((affected, last_insert_id)) => {
+                result.status = "applied".to_string();
+                result.affected_rows = affected;
+                result.last_insert_id = last_insert_id;
+            }
+            Err(e) => {
+                result.status = "failed".to_string();
+                result.error = Some(e);
+                failed = Some(i);
+                break;
+            }
+        }
+    }
+
+    let Some(failed) = failed else {
+        conn.query_drop(commit).await.map_err(|e| e.to_string())?;
+        return Ok(ChangesetResult { dry_run, applied: true, operations: results, rollback_error: None });
+    };
+
+    // The results are still worth returning if this fails, so the error is reported with them
+    let mut rollback_error = conn.query_drop(rollback).await.err().map(|e| e.to_string());
+    if pinned && rollback_error.is_none() {
+        rollback_error = conn.query_drop(format!("RELEASE SAVEPOINT {}", SAVEPOINT)).await.err().map(|e| e.to_string());
+    }
+    if let Some(e) = rollback_error.as_mut() {
+        log::warn!("Changeset rollback failed: {}", e);
+        if pinned {
+            *e = format!("{}. The open transaction may have been rolled back entirely", e);
+        }
+    }
+    for result in results.iter_mut() {
+        if result.index < failed {
+            result.status = "rolled_back".to_string();
+            result.affected_rows = 0;
+            result.last_insert_id = None;
+        } else if result.index > failed {
+            result.status = "skipped".to_string();
+        }
+    }
+    Ok(ChangesetResult { dry_run, applied: false, operations: results, rollback_error })
+}
+
+fn plan_operation(op: &ChangeOperation, columns: &[ColumnInfo]) -> Result<Planned, String> {
+    let (sql, params) = match op {
+        ChangeOperation::Insert { db, table, values } => {
+            insert_statement(db, table, std::slice::from_ref(values), columns)?
+        }
+        ChangeOperation::Update { db, table, key, values, original } => {
+            if values.is_empty() {
+                return Err("Nothing to update".to_string());
+            }
+            update_statement(db, table, values, key, original.as_ref(), columns)?
+        }
+        ChangeOperation::Delete { db, table, key } => delete_statement(db, table, key, columns)?,
+    };
+    Ok(Planned { sql, params })
+}
+
+/// Runs one statement. Updates and deletes that find no row count as failures, since the
+/// batch was built against rows that are no longer there as loaded.
+async fn execute(conn: &mut mysql_async::Conn, op: &ChangeOperation, planned: Planned) -> Result<(u64, Option<u64>), String> {
+    conn.exec_drop(planned.sql, planned.params).await.map_err(|e| e.to_string())?;
+    match op {
+        ChangeOperation::Insert { .. } => Ok((conn.affected_rows(), conn.last_insert_id())),
+        ChangeOperation::Update { original, .. } => {
+            if rows_matched(conn) == 0 {
+                return Err(if original.is_some() {
+                    "Row was changed or deleted since it was loaded".to_string()
+                } else {
+                    "Row not found".to_string()
+                });
+            }
+            Ok((conn.affected_rows(), None))
+        }
+        ChangeOperation::Delete { .. } => match conn.affected_rows() {
+            0 => Err("Row not found".to_string()),
+            n => Ok((n, None)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::column;
+    use serde_json::json;
+
+    fn columns() -> Vec<ColumnInfo> {
+        vec![column("id", "int"), column("name", "varchar(20)")]
+    }
+
+    fn operation(value: serde_json::Value) -> ChangeOperation {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn plans_each_kind_of_operation() {
+        let insert = operation(json!({"type": "insert", "db": "d", "table": "t", "values": {"name": "a"}}));
+        let planned = plan_operation(&insert, &columns()).unwrap();
+        assert_eq!(planned.sql, "INSERT INTO `d`.`t` (`name`) VALUES (?)");
+        assert_eq!(planned.params, vec![mysql_async::Value::Bytes(b"a".to_vec())]);
+
+        let update = operation(json!({"type": "update", "db": "d", "table": "t", "key": {"id": 3}, "values": {"name": "b"}}));
+        let planned = plan_operation(&update, &columns()).unwrap();
+        assert_eq!(planned.sql, "UPDATE `d`.`t` SET `name` = ? WHERE `id` <=> ? LIMIT 1");
+        assert_eq!(planned.params.len(), 2);
+
+        let delete = operation(json!({"type": "delete", "db": "d", "table": "t", "key": {"id": 3}}));
+        let planned = plan_operation(&delete, &columns()).unwrap();
+        assert_eq!(planned.sql, "DELETE FROM `d`.`t` WHERE `id` <=> ? LIMIT 1");
+        assert_eq!(planned.params, vec![mysql_async::Value::Int(3)]);
+    }
+
+    #[test]
+    fn rejects_operations_that_cannot_run() {
+        let empty = operation(json!({"type": "update", "db": "d", "table": "t", "key": {"id": 3}, "values": {}}));
+        assert_eq!(plan_operation(&empty, &columns()).err().unwrap(), "Nothing to update");
+
+        let no_key = operation(json!({"type": "delete", "db": "d", "table": "t", "key": {}}));
+        assert!(plan_operation(&no_key, &columns()).is_err());
+
+        let bad_value = operation(json!({"type": "insert", "db": "d", "table": "t", "values": {"id": "x"}}));
+        assert!(plan_operation(&bad_value, &columns()).err().unwrap().contains("`id`"));
+
+        let unknown = operation(json!({"type": "update", "db": "d", "table": "t", "key": {"id": 3}, "values": {"missing": 1}}));
+        assert!(plan_operation(&unknown, &columns()).is_err());
+    }
+}
@@ -111,3 +111,81 @@ pub fn render_table_html(columns: &[String], rows: &[Vec<serde_json::Value>]) ->
 
     (head, body)
 }
+
+/// Server product and version, for picking syntax that differs between MySQL and MariaDB.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerVersion {
+    pub is_mariadb: bool,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    /// Parses `SELECT VERSION()` output such as `8.0.36` or `10.11.6-MariaDB-log`.
+    pub fn parse(version: &str) -> Self {
+        let is_mariadb = version.to_lowercase().contains("mariadb");
+        // Older MariaDB clients and proxies report `5.5.5-10.x` for replication compatibility
+        let version = match version.strip_prefix("5.5.5-") {
+            Some(rest) if is_mariadb => rest,
+            _ => version,
+        };
+        let mut parts = version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse().unwrap_or(0));
+        ServerVersion {
+            is_mariadb,
+            major: parts.next().unwrap_or(0),
+            minor: parts.next().unwrap_or(0),
+            patch: parts.next().unwrap_or(0),
+        }
+    }
+
+    pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+}
+
+pub async fn server_version(conn: &mut mysql_async::Conn) -> Result<ServerVersion, String> {
+    use mysql_async::prelude::Queryable;
+    let version: Option<String> = conn.query_first("SELECT VERSION()").await.map_err(|e| e.to_string())?;
+    Ok(ServerVersion::parse(&version.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_server_versions() {
+        let mysql = ServerVersion::parse("8.0.36-log");
+        assert!(!mysql.is_mariadb);
+        assert_eq!((mysql.major, mysql.minor, mysql.patch), (8, 0, 36));
+        assert!(mysql.at_least(8, 0, 18));
+        assert!(!mysql.at_least(8, 1, 0));
+
+        let mariadb = ServerVersion::parse("10.11.6-MariaDB");
+        assert!(mariadb.is_mariadb);
+        assert_eq!((mariadb.major, mariadb.minor, mariadb.patch), (10, 11, 6));
+
+        let ubuntu = ServerVersion::parse("10.6.16-MariaDB-0ubuntu0.22.04.1-log");
+        assert!(ubuntu.is_mariadb);
+        assert_eq!((ubuntu.major, ubuntu.minor, ubuntu.patch), (10, 6, 16));
+    }
+
+    #[test]
+    fn skips_mariadb_replication_prefix() {
+        let version = ServerVersion::parse("5.5.5-10.11.6-MariaDB");
+        assert!(version.is_mariadb);
+        assert_eq!((version.major, version.minor, version.patch), (10, 11, 6));
+    }
+
+    #[test]
+    fn tolerates_unexpected_versions() {
+        let version = ServerVersion::parse("");
+        assert_eq!((version.major, version.minor, version.patch), (0, 0, 0));
+        let short = ServerVersion::parse("9.1");
+        assert_eq!((short.major, short.minor, short.patch), (9, 1, 0));
+    }
+}
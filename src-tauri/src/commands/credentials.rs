@@ -0,0 +1,109 @@
+use tauri::{AppHandle, Manager, State};
+use crate::state::AppState;
+use crate::secret_store::{EncryptedFileStore, HasSecrets, SecretStore};
+use crate::server_profiles::ProfileRepository;
+use serde::Serialize;
+
+const VAULT_FILE: &str = "secrets.json";
+
+#[derive(Serialize)]
+pub struct VaultStatus {
+    /// A vault file exists, so unlocking needs the existing master password
+    pub initialized: bool,
+    pub unlocked: bool,
+}
+
+#[tauri::command]
+pub fn get_vault_status(app_handle: AppHandle, state: State<'_, AppState>) -> Result<VaultStatus, String> {
+    let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(VaultStatus {
+        initialized: EncryptedFileStore::exists(&config_dir.join(VAULT_FILE)),
+        unlocked: state.secret_store.lock().unwrap().is_some(),
+    })
+}
+
+/// Opens the credential vault (creating it on first use) and moves any plaintext
+/// secrets still sitting in `servers.json` into it.
+#[tauri::command]
+pub async fn unlock_vault(master_password: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    if !config_dir.exists() {
+        std::fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    }
+
+    // Argon2 is deliberately slow, keep it off the async runtime
+    let vault_path = config_dir.join(VAULT_FILE);
+    let mut store = tauri::async_runtime::spawn_blocking(move || EncryptedFileStore::open(&vault_path, &master_password))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    migrate_plaintext_secrets(&ProfileRepository::new(&config_dir), &mut store)?;
+
+    *state.secret_store.lock().unwrap() = Some(Box::new(store));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_vault(state: State<'_, AppState>) -> Result<(), String> {
+    *state.secret_store.lock().unwrap() = None;
+    Ok(())
+}
+
+/// One-time migration for server lists saved before the vault existed.
+fn migrate_plaintext_secrets(repo: &ProfileRepository, store: &mut dyn SecretStore) -> Result<(), String> {
+    let mut servers = repo.load()?;
+
+    let mut migrated = false;
+    for server in servers.iter_mut() {
+        let secrets = server.take_secrets();
+        if secrets.is_empty() {
+            continue;
+        }
+
+        let mut merged = store.get(&server.id)?.unwrap_or_default();
+        merged.merge(secrets);
+        store.put(&server.id, &merged)?;
+        migrated = true;
+    }
+
+    if migrated {
+        // Secrets are safely in the vault now, rewrite the list without them
+        repo.save(&servers)?;
+        log::info!("Moved plaintext server credentials into the vault");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server_profiles::ServerProfile;
+
+    #[test]
+    fn moves_plaintext_secrets_into_the_vault() {
+        let dir = std::env::temp_dir().join(format!("omnimin-credentials-{}", uuid::Uuid::new_v4()));
+        let repo = ProfileRepository::new(&dir);
+        repo.save(&[ServerProfile {
+            id: "s1".to_string(),
+            name: "local".to_string(),
+            pass: Some("db-pass".to_string()),
+            ssh_pass: Some("ssh-pass".to_string()),
+            ..Default::default()
+        }])
+        .unwrap();
+
+        let mut store = EncryptedFileStore::open(&dir.join(VAULT_FILE), "master").unwrap();
+        migrate_plaintext_secrets(&repo, &mut store).unwrap();
+
+        let saved = repo.load().unwrap();
+        assert!(saved[0].pass.is_none() && saved[0].ssh_pass.is_none());
+        let secrets = store.get("s1").unwrap().unwrap();
+        assert_eq!(secrets.pass.as_deref(), Some("db-pass"));
+        assert_eq!(secrets.ssh_pass.as_deref(), Some("ssh-pass"));
+
+        // A second run finds nothing left to move and keeps the vault entry
+        migrate_plaintext_secrets(&repo, &mut store).unwrap();
+        assert_eq!(store.get("s1").unwrap().unwrap().pass.as_deref(), Some("db-pass"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -12,11 +12,8 @@ pub struct DatabaseInfo {
 }
 
 #[tauri::command]
-pub async fn get_databases(state: State<'_, AppState>) -> Result<Vec<DatabaseInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_databases(session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<DatabaseInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     // OPTIMIZED: Only fetch essential fields, no expensive JOINs
@@ -46,11 +43,8 @@ pub async fn get_databases(state: State<'_, AppState>) -> Result<Vec<DatabaseInf
 }
 
 #[tauri::command]
-pub async fn create_database(name: String, collation: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn create_database(name: String, collation: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let collation_sql = if let Some(c) = collation {
@@ -61,22 +55,16 @@ pub async fn create_database(name: String, collation: Option<String>, state: Sta
 }
 
 #[tauri::command]
-pub async fn drop_database(name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_database(name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     conn.query_drop(format!("DROP DATABASE `{}`", name)).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_collations(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_collations(session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let collations: Vec<String> = conn.query("SELECT COLLATION_NAME FROM information_schema.COLLATIONS ORDER BY COLLATION_NAME")
@@ -86,27 +74,21 @@ pub async fn get_collations(state: State<'_, AppState>) -> Result<Vec<String>, S
 }
 
 #[tauri::command]
-pub async fn change_collation(db: String, collation: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn change_collation(db: String, collation: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     conn.query_drop(format!("ALTER DATABASE `{}` COLLATE {}", db, collation)).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn alter_database_collation(db: String, collation: String, state: State<'_, AppState>) -> Result<(), String> {
-    change_collation(db, collation, state).await
+pub async fn alter_database_collation(db: String, collation: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    change_collation(db, collation, session_id, state).await
 }
 
 #[tauri::command]
-pub async fn rename_database(name: String, new_name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn rename_database(name: String, new_name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     conn.query_drop(format!("CREATE DATABASE `{}`", new_name)).await.map_err(|e| e.to_string())?;
@@ -128,11 +110,8 @@ pub async fn rename_database(name: String, new_name: String, state: State<'_, Ap
 }
 
 #[tauri::command]
-pub async fn copy_database(name: String, new_name: String, with_data: bool, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn copy_database(name: String, new_name: String, with_data: bool, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     conn.query_drop(format!("CREATE DATABASE `{}`", new_name)).await.map_err(|e| e.to_string())?;
@@ -156,11 +135,8 @@ pub async fn copy_database(name: String, new_name: String, with_data: bool, stat
 
 // NEW: On-demand database statistics (called only when needed)
 #[tauri::command]
-pub async fn get_database_stats(db_name: String, state: State<'_, AppState>) -> Result<(u64, u64), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_database_stats(db_name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(u64, u64), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let sql = format!("
@@ -0,0 +1,583 @@
+use tauri::State;
+use crate::state::AppState;
+use crate::commands::common::server_version;
+use crate::sql_splitter::split_sql;
+use mysql_async::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+/// One operation of an execution plan. Tables are leaves; nested loops, sorts,
+/// groupings, unions and subqueries wrap their inputs in `children`.
+#[derive(Serialize, Default, Debug)]
+pub struct PlanNode {
+    pub operation: String, // "query_block" | "table" | "nested_loop" | "ordering_operation" | "filesort" | ...
+    pub table: Option<String>,
+    pub access_type: Option<String>, // "ALL" | "index" | "range" | "ref" | "eq_ref" | "const" | ...
+    pub possible_keys: Vec<String>,
+    pub key: Option<String>,
+    pub used_key_parts: Vec<String>,
+    pub rows_examined: Option<f64>,
+    pub rows_produced: Option<f64>,
+    pub filtered: Option<f64>,
+    pub cost: Option<f64>,
+    pub condition: Option<String>,
+    pub using_index: bool,
+    pub using_temporary: bool,
+    pub using_filesort: bool,
+    /// Measured by MariaDB's ANALYZE FORMAT=JSON
+    pub actual_rows: Option<f64>,
+    pub actual_loops: Option<f64>,
+    pub actual_time_ms: Option<f64>,
+    /// Full scans, unused or missing indexes, temporary tables and filesorts
+    pub warnings: Vec<String>,
+    pub children: Vec<PlanNode>,
+}
+
+/// A line of MySQL's `EXPLAIN ANALYZE` tree output.
+#[derive(Serialize, Default, Debug)]
+pub struct AnalyzeNode {
+    pub operation: String,
+    pub estimated_cost: Option<f64>,
+    pub estimated_rows: Option<f64>,
+    pub actual_first_row_ms: Option<f64>,
+    pub actual_last_row_ms: Option<f64>,
+    pub actual_rows: Option<f64>,
+    pub loops: Option<f64>,
+    pub children: Vec<AnalyzeNode>,
+}
+
+#[derive(Serialize)]
+pub struct QueryPlan {
+    pub server: String, // "mysql" | "mariadb"
+    /// The statement was executed to collect actual timings and row counts
+    pub analyzed: bool,
+    pub total_cost: Option<f64>,
+    pub root: PlanNode,
+    /// MySQL 8.0.18+ only; MariaDB folds the measurements into `root`
+    pub analyze_tree: Option<AnalyzeNode>,
+    /// Warnings of all nodes, deduplicated
+    pub warnings: Vec<String>,
+    pub raw_json: Value,
+}
+
+#[tauri::command]
+pub async fn explain_query_plan(sql: String, db: Option<String>, analyze: Option<bool>, session_id: Option<String>, state: State<'_, AppState>) -> Result<QueryPlan, String> {
+    let sql = single_statement(&sql)?;
+    let analyze = analyze.unwrap_or(false);
+    let mut conn = state.get_conn(session_id.as_deref()).await?;
+
+    if let Some(db_name) = db.filter(|d| !d.is_empty()) {
+        conn.query_drop(format!("USE `{}`", db_name)).await.map_err(|e| e.to_string())?;
+    }
+
+    let version = server_version(&mut conn).await?;
+
+    if analyze {
+        // ANALYZE really runs the statement, an UPDATE would change data
+        if !is_query(&sql) {
+            return Err("EXPLAIN ANALYZE executes the statement; only SELECT queries can be analyzed".to_string());
+        }
+        if !version.is_mariadb && !version.at_least(8, 0, 18) {
+            return Err("EXPLAIN ANALYZE requires MySQL 8.0.18 or newer".to_string());
+        }
+    }
+
+    let json_sql = if analyze && version.is_mariadb {
+        format!("ANALYZE FORMAT=JSON {}", sql)
+    } else {
+        format!("EXPLAIN FORMAT=JSON {}", sql)
+    };
+    let json_text: Option<String> = conn.query_first(json_sql).await.map_err(|e| format!("SQL Error: {}", e))?;
+    let raw_json: Value = serde_json::from_str(&json_text.ok_or("EXPLAIN returned no plan")?)
+        .map_err(|e| format!("Cannot parse plan: {}", e))?;
+
+    let analyze_tree = if analyze && !version.is_mariadb {
+        let tree: Vec<String> = conn.query(format!("EXPLAIN ANALYZE {}", sql)).await.map_err(|e| format!("SQL Error: {}", e))?;
+        parse_analyze_tree(&tree.join("\n"))
+    } else {
+        None
+    };
+
+    let root = match raw_json.get("query_block") {
+        Some(block) => parse_block(block),
+        None => parse_block(&raw_json),
+    };
+    let mut warnings = Vec::new();
+    collect_warnings(&root, &mut warnings);
+
+    Ok(QueryPlan {
+        server: if version.is_mariadb { "mariadb" } else { "mysql" }.to_string(),
+        analyzed: analyze,
+        total_cost: root.cost,
+        root,
+        analyze_tree,
+        warnings,
+        raw_json,
+    })
+}
+
+/// The one statement in `sql`. The EXPLAIN is sent as text, where anything after a `;`
+/// would run as a statement of its own.
+fn single_statement(sql: &str) -> Result<String, String> {
+    let mut statements = split_sql(sql);
+    match statements.len() {
+        0 => Err("Nothing to explain".to_string()),
+        1 => Ok(statements.remove(0).sql),
+        _ => Err("Only a single statement can be explained".to_string()),
+    }
+}
+
+/// Whether `sql` only reads: a SELECT, TABLE or parenthesised query, after any WITH clause.
+fn is_query(sql: &str) -> bool {
+    let body = skip_with_clause(sql).trim_start();
+    body.starts_with('(') || ["SELECT", "TABLE"].iter().any(|kw| starts_with_word(body, kw))
+}
+
+fn starts_with_word(text: &str, word: &str) -> bool {
+    text.len() >= word.len()
+        && text.as_bytes()[..word.len()].eq_ignore_ascii_case(word.as_bytes())
+        && !text[word.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The statement a `WITH` clause belongs to, i.e. the text after the last common table
+/// expression. Text without a WITH clause is returned as it is.
+fn skip_with_clause(sql: &str) -> &str {
+    let sql = sql.trim_start();
+    if !starts_with_word(sql, "WITH") {
+        return sql;
+    }
+
+    let bytes = sql.as_bytes();
+    let mut depth = 0usize;
+    let mut last_word = String::new();
+    // Inside the parenthesised body of a CTE, as opposed to its column list
+    let mut in_body = false;
+    let mut i = 4;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match c {
+            b'\'' | b'"' | b'`' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != c {
+                    if bytes[i] == b'\\' && c != b'`' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 1);
+            }
+            b'(' => {
+                if depth == 0 {
+                    in_body = last_word.eq_ignore_ascii_case("AS");
+                }
+                depth += 1;
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 && in_body {
+                    in_body = false;
+                    let rest = sql[i + 1..].trim_start();
+                    match rest.strip_prefix(',') {
+                        Some(_) => last_word.clear(),
+                        None => return rest,
+                    }
+                }
+            }
+            _ if depth == 0 && (c.is_ascii_alphanumeric() || c == b'_') => {
+                let start = i;
+                while i + 1 < bytes.len() && (bytes[i + 1].is_ascii_alphanumeric() || bytes[i + 1] == b'_') {
+                    i += 1;
+                }
+                last_word = sql[start..=i].to_string();
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    ""
+}
+
+/// JSON plans mix numbers and numeric strings ("filtered": "10.00")
+fn num(v: Option<&Value>) -> Option<f64> {
+    match v? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|s| s.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
+}
+
+fn flag(v: &Value, key: &str) -> bool {
+    v.get(key).and_then(|f| f.as_bool()).unwrap_or(false)
+}
+
+fn parse_block(v: &Value) -> PlanNode {
+    PlanNode {
+        operation: "query_block".to_string(),
+        cost: num(v.pointer("/cost_info/query_cost")).or_else(|| num(v.get("cost"))),
+        actual_time_ms: num(v.get("r_total_time_ms")),
+        children: collect_children(v),
+        ..Default::default()
+    }
+}
+
+fn collect_children(v: &Value) -> Vec<PlanNode> {
+    let mut out = Vec::new();
+    let Some(obj) = v.as_object() else { return out };
+
+    for (key, val) in obj {
+        match key.as_str() {
+            "query_block" => out.push(parse_block(val)),
+            "table" => out.push(parse_table(val)),
+            "nested_loop" => out.push(PlanNode {
+                operation: "nested_loop".to_string(),
+                children: val.as_array().into_iter().flatten().flat_map(collect_children).collect(),
+                ..Default::default()
+            }),
+            // MySQL wraps inputs in these, MariaDB uses filesort/temporary_table
+            "ordering_operation" | "grouping_operation" | "duplicates_removal" | "windowing"
+            | "filesort" | "temporary_table" | "read_sorted_file" => {
+                let mut node = PlanNode {
+                    operation: key.clone(),
+                    using_filesort: key == "filesort" || flag(val, "using_filesort"),
+                    using_temporary: key == "temporary_table" || flag(val, "using_temporary_table"),
+                    actual_time_ms: num(val.get("r_total_time_ms")),
+                    children: collect_children(val),
+                    ..Default::default()
+                };
+                add_flag_warnings(&mut node);
+                out.push(node);
+            }
+            "union_result" => {
+                let mut node = PlanNode {
+                    operation: "union_result".to_string(),
+                    table: val.get("table_name").and_then(|t| t.as_str()).map(|t| t.to_string()),
+                    using_temporary: flag(val, "using_temporary_table"),
+                    children: collect_children(val),
+                    ..Default::default()
+                };
+                add_flag_warnings(&mut node);
+                out.push(node);
+            }
+            "query_specifications" | "attached_subqueries" | "optimized_away_subqueries" | "subqueries" => {
+                for item in val.as_array().into_iter().flatten() {
+                    out.extend(collect_children(item));
+                }
+            }
+            "materialized_from_subquery" | "subquery" | "materialized" => out.push(PlanNode {
+                operation: key.clone(),
+                children: collect_children(val),
+                ..Default::default()
+            }),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn parse_table(v: &Value) -> PlanNode {
+    let mut node = PlanNode {
+        operation: "table".to_string(),
+        table: v.get("table_name").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        access_type: v.get("access_type").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        possible_keys: string_list(v.get("possible_keys")),
+        key: v.get("key").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        used_key_parts: string_list(v.get("used_key_parts")),
+        // MySQL: rows_examined_per_scan, MariaDB: rows
+        rows_examined: num(v.get("rows_examined_per_scan")).or_else(|| num(v.get("rows"))),
+        rows_produced: num(v.get("rows_produced_per_join")),
+        filtered: num(v.get("filtered")),
+        cost: num(v.pointer("/cost_info/prefix_cost"))
+            .or_else(|| num(v.pointer("/cost_info/read_cost")))
+            .or_else(|| num(v.get("cost"))),
+        condition: v.get("attached_condition").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        using_index: flag(v, "using_index"),
+        using_temporary: flag(v, "using_temporary_table"),
+        using_filesort: flag(v, "using_filesort"),
+        actual_rows: num(v.get("r_rows")),
+        actual_loops: num(v.get("r_loops")),
+        actual_time_ms: num(v.get("r_total_time_ms")).or_else(|| num(v.get("r_table_time_ms"))),
+        children: collect_children(v),
+        ..Default::default()
+    };
+
+    let name = node.table.clone().unwrap_or_default();
+    match node.access_type.as_deref() {
+        Some("ALL") if node.possible_keys.is_empty() && node.condition.is_some() => {
+            node.warnings.push(format!("Full table scan on `{}`: no index matches the filter (missing index?)", name));
+        }
+        Some("ALL") => node.warnings.push(format!("Full table scan on `{}`", name)),
+        Some("index") if !node.using_index => node.warnings.push(format!("Full index scan on `{}`", name)),
+        _ => {}
+    }
+    if !node.possible_keys.is_empty() && node.key.is_none() {
+        node.warnings.push(format!(
+            "`{}` has candidate indexes ({}) but none is used",
+            name,
+            node.possible_keys.join(", ")
+        ));
+    }
+    add_flag_warnings(&mut node);
+    node
+}
+
+fn add_flag_warnings(node: &mut PlanNode) {
+    if node.using_temporary {
+        node.warnings.push("Uses a temporary table".to_string());
+    }
+    if node.using_filesort {
+        node.warnings.push("Uses filesort".to_string());
+    }
+}
+
+fn collect_warnings(node: &PlanNode, out: &mut Vec<String>) {
+    for w in &node.warnings {
+        if !out.contains(w) {
+            out.push(w.clone());
+        }
+    }
+    for child in &node.children {
+        collect_warnings(child, out);
+    }
+}
+
+/// Parses MySQL's indented `-> operation (cost=.. rows=..) (actual time=a..b rows=.. loops=..)` tree.
+fn parse_analyze_tree(text: &str) -> Option<AnalyzeNode> {
+    // Stack of (indent, node); a line closes every open node at the same or deeper indent
+    let mut stack: Vec<(usize, AnalyzeNode)> = Vec::new();
+    let mut root: Option<AnalyzeNode> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let Some(body) = trimmed.strip_prefix("-> ") else { continue };
+        let indent = line.len() - trimmed.len();
+        let node = parse_analyze_line(body);
+
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            let (_, done) = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(done),
+                None => root = Some(done),
+            }
+        }
+        stack.push((indent, node));
+    }
+
+    while let Some((_, done)) = stack.pop() {
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.push(done),
+            None => root = Some(done),
+        }
+    }
+    root
+}
+
+fn parse_analyze_line(body: &str) -> AnalyzeNode {
+    let mut node = AnalyzeNode::default();
+    let mut operation = body;
+
+    if let Some(pos) = body.find("(cost=") {
+        operation = &body[..pos];
+        let part = &body[pos + 1..];
+        let part = &part[..part.find(')').unwrap_or(part.len())];
+        node.estimated_cost = field(part, "cost=");
+        node.estimated_rows = field(part, "rows=");
+    }
+    if let Some(pos) = body.find("(actual time=") {
+        if operation.len() > pos {
+            operation = &body[..pos];
+        }
+        let part = &body[pos + "(actual time=".len()..];
+        let part = &part[..part.find(')').unwrap_or(part.len())];
+        let times = part.split_whitespace().next().unwrap_or("");
+        let mut bounds = times.split("..");
+        node.actual_first_row_ms = bounds.next().and_then(|t| t.parse().ok());
+        node.actual_last_row_ms = bounds.next().and_then(|t| t.parse().ok());
+        node.actual_rows = field(part, "rows=");
+        node.loops = field(part, "loops=");
+    }
+
+    node.operation = operation.trim().to_string();
+    node
+}
+
+fn field(text: &str, name: &str) -> Option<f64> {
+    let start = text.find(name)? + name.len();
+    text[start..].split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_exactly_one_statement() {
+        assert_eq!(single_statement("  SELECT 1; -- done\n").unwrap(), "SELECT 1");
+        assert_eq!(single_statement("SELECT ';' AS s").unwrap(), "SELECT ';' AS s");
+        assert!(single_statement("SELECT 1; DROP TABLE t").is_err());
+        assert!(single_statement("SELECT 1;\nDROP TABLE t;").is_err());
+        assert!(single_statement(" ; ").is_err());
+    }
+
+    #[test]
+    fn analyzes_only_queries() {
+        assert!(is_query("select * FROM t"));
+        assert!(is_query("TABLE t"));
+        assert!(is_query("(SELECT 1) UNION (SELECT 2)"));
+        assert!(is_query("WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(is_query(
+            "WITH RECURSIVE a (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM a WHERE n < 3), b AS (SELECT ')' AS p) SELECT * FROM a, b"
+        ));
+
+        assert!(!is_query("UPDATE t SET a = 1"));
+        assert!(!is_query("SELECTED"));
+        assert!(!is_query("WITH x AS (SELECT 1) DELETE FROM t WHERE id IN (SELECT * FROM x)"));
+        assert!(!is_query("with x as (select 1), y as (select 2) update t set a = 1"));
+        assert!(!is_query("WITH x AS (SELECT 'a)' /* ) */) DELETE FROM t"));
+        assert!(!is_query("WITH x AS (SELECT 1"));
+    }
+
+    const MYSQL_JSON: &str = r#"{
+      "query_block": {
+        "select_id": 1,
+        "cost_info": { "query_cost": "12.75" },
+        "ordering_operation": {
+          "using_filesort": true,
+          "nested_loop": [
+            { "table": {
+                "table_name": "o", "access_type": "ALL", "possible_keys": ["idx_customer"],
+                "rows_examined_per_scan": 100, "rows_produced_per_join": 10, "filtered": "10.00",
+                "cost_info": { "read_cost": "9.50", "prefix_cost": "10.50" },
+                "attached_condition": "(`shop`.`o`.`status` = 'open')" } },
+            { "table": {
+                "table_name": "c", "access_type": "eq_ref", "possible_keys": ["PRIMARY"],
+                "key": "PRIMARY", "used_key_parts": ["id"], "rows_examined_per_scan": 1,
+                "filtered": "100.00", "using_index": true,
+                "cost_info": { "prefix_cost": "12.75" } } }
+          ]
+        }
+      }
+    }"#;
+
+    const MARIADB_ANALYZE_JSON: &str = r#"{
+      "query_block": {
+        "select_id": 1, "r_loops": 1, "r_total_time_ms": 0.52,
+        "filesort": {
+          "sort_key": "t.a", "r_loops": 1, "r_total_time_ms": 0.11,
+          "temporary_table": {
+            "table": {
+              "table_name": "t", "access_type": "ALL", "rows": 1000, "r_rows": 998,
+              "r_loops": 1, "r_table_time_ms": 0.31, "filtered": 100, "r_filtered": 50,
+              "attached_condition": "t.b > 1"
+            }
+          }
+        }
+      }
+    }"#;
+
+    const MYSQL_ANALYZE_TREE: &str = "\
+-> Sort: c.name  (cost=2.50 rows=10) (actual time=0.100..0.120 rows=8 loops=1)
+    -> Nested loop inner join  (cost=1.50 rows=10) (actual time=0.050..0.090 rows=8 loops=1)
+        -> Table scan on o  (cost=0.75 rows=10) (actual time=0.020..0.030 rows=10 loops=1)
+        -> Single-row index lookup on c using PRIMARY (id=o.customer_id)  (cost=0.25 rows=1) (actual time=0.002..0.002 rows=1 loops=10)";
+
+    fn root_of(json: &str) -> PlanNode {
+        let raw: Value = serde_json::from_str(json).unwrap();
+        parse_block(raw.get("query_block").unwrap())
+    }
+
+    #[test]
+    fn parses_mysql_json_plan() {
+        let root = root_of(MYSQL_JSON);
+        assert_eq!(root.cost, Some(12.75));
+
+        let ordering = &root.children[0];
+        assert_eq!(ordering.operation, "ordering_operation");
+        assert!(ordering.using_filesort);
+        assert_eq!(ordering.warnings, vec!["Uses filesort".to_string()]);
+
+        let join = &ordering.children[0];
+        assert_eq!(join.operation, "nested_loop");
+        let (orders, customers) = (&join.children[0], &join.children[1]);
+
+        assert_eq!(orders.table.as_deref(), Some("o"));
+        assert_eq!(orders.rows_examined, Some(100.0));
+        assert_eq!(orders.rows_produced, Some(10.0));
+        assert_eq!(orders.filtered, Some(10.0));
+        assert_eq!(orders.cost, Some(10.5));
+        assert_eq!(orders.warnings.len(), 2);
+        assert!(orders.warnings[0].starts_with("Full table scan on `o`"));
+        assert!(orders.warnings[1].contains("candidate indexes (idx_customer)"));
+
+        assert_eq!(customers.key.as_deref(), Some("PRIMARY"));
+        assert_eq!(customers.used_key_parts, vec!["id".to_string()]);
+        assert!(customers.using_index);
+        assert!(customers.warnings.is_empty());
+
+        let mut warnings = Vec::new();
+        collect_warnings(&root, &mut warnings);
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn parses_mariadb_analyze_json() {
+        let root = root_of(MARIADB_ANALYZE_JSON);
+        assert_eq!(root.actual_time_ms, Some(0.52));
+
+        let filesort = &root.children[0];
+        assert_eq!(filesort.operation, "filesort");
+        assert!(filesort.using_filesort);
+        let temporary = &filesort.children[0];
+        assert_eq!(temporary.operation, "temporary_table");
+        assert!(temporary.using_temporary);
+
+        let table = &temporary.children[0];
+        assert_eq!(table.table.as_deref(), Some("t"));
+        assert_eq!(table.rows_examined, Some(1000.0));
+        assert_eq!(table.actual_rows, Some(998.0));
+        assert_eq!(table.actual_loops, Some(1.0));
+        assert_eq!(table.actual_time_ms, Some(0.31));
+        assert_eq!(table.filtered, Some(100.0));
+    }
+
+    #[test]
+    fn parses_mysql_analyze_tree() {
+        let tree = parse_analyze_tree(MYSQL_ANALYZE_TREE).unwrap();
+        assert_eq!(tree.operation, "Sort: c.name");
+        assert_eq!(tree.estimated_cost, Some(2.5));
+        assert_eq!(tree.estimated_rows, Some(10.0));
+        assert_eq!(tree.actual_first_row_ms, Some(0.1));
+        assert_eq!(tree.actual_last_row_ms, Some(0.12));
+
+        let join = &tree.children[0];
+        assert_eq!(join.operation, "Nested loop inner join");
+        assert_eq!(join.children.len(), 2);
+        let lookup = &join.children[1];
+        assert_eq!(lookup.operation, "Single-row index lookup on c using PRIMARY (id=o.customer_id)");
+        assert_eq!(lookup.loops, Some(10.0));
+        assert_eq!(lookup.actual_rows, Some(1.0));
+    }
+
+    #[test]
+    fn parses_lines_without_estimates() {
+        // MySQL leaves the cost out for some operations, e.g. a LIMIT on top of the plan
+        let node = parse_analyze_line("Limit: 10 row(s)  (actual time=0.001..0.004 rows=0 loops=1)");
+        assert_eq!(node.operation, "Limit: 10 row(s)");
+        assert_eq!(node.estimated_cost, None);
+        assert_eq!(node.actual_last_row_ms, Some(0.004));
+        assert_eq!(node.actual_rows, Some(0.0));
+    }
+
+    #[test]
+    fn ignores_text_without_tree_lines() {
+        assert!(parse_analyze_tree("").is_none());
+        assert!(parse_analyze_tree("EXPLAIN\nnot a plan").is_none());
+    }
+}
@@ -0,0 +1,519 @@
+use crate::commands::row_values::{placeholder, to_column_value};
+use crate::commands::table::{find_column, quote_ident, ColumnInfo, Filter};
+use mysql_async::Value;
+use serde::{Deserialize, Serialize};
+
+/// Escape character for LIKE patterns built from plain text. Not a backslash, so the
+/// pattern means the same with or without NO_BACKSLASH_ESCAPES.
+const LIKE_ESCAPE: char = '!';
+
+/// A browse filter: conditions combined by nested AND/OR groups. Serialisable so saved
+/// filters can be stored and reused as they are.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterNode {
+    Group {
+        combinator: Combinator,
+        /// Wraps the whole group in NOT
+        #[serde(default)]
+        negated: bool,
+        children: Vec<FilterNode>,
+    },
+    Condition(FilterCondition),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Combinator {
+    And,
+    Or,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FilterCondition {
+    pub column: String,
+    /// Filter on an expression of the column rather than the column itself
+    #[serde(default)]
+    pub expr: Option<ColumnExpr>,
+    pub op: FilterOp,
+    /// Operand of single-value operators
+    #[serde(default)]
+    pub value: Option<serde_json::Value>,
+    /// Operands of `in`/`not_in`, or the two bounds of `between`/`not_between`
+    #[serde(default)]
+    pub values: Vec<serde_json::Value>,
+    /// How `like`/`not_like` treat the value; `pattern` when absent
+    #[serde(default)]
+    pub like_mode: Option<LikeMode>,
+    /// Required by `date_range`
+    #[serde(default)]
+    pub date_range: Option<DateRange>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ColumnExpr {
+    /// `JSON_UNQUOTE(JSON_EXTRACT(col, path))`, e.g. path `$.address.city`
+    JsonPath { path: String },
+    Lower,
+    Upper,
+    Trim,
+    Length,
+    Date,
+    Year,
+    Month,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Like,
+    NotLike,
+    In,
+    NotIn,
+    Between,
+    NotBetween,
+    IsNull,
+    IsNotNull,
+    Regexp,
+    NotRegexp,
+    DateRange,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LikeMode {
+    /// The value is a LIKE pattern, `%` and `_` included
+    Pattern,
+    Contains,
+    StartsWith,
+    EndsWith,
+    /// Whole value, wildcards matched literally
+    Exact,
+}
+
+/// Day-based ranges, evaluated against the server's current date. Ends are exclusive so
+/// DATETIME columns include the whole last day.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DateRange {
+    Today,
+    Yesterday,
+    /// The last `days` days, today included
+    LastDays { days: u32 },
+    /// Monday to Sunday of the current week
+    ThisWeek,
+    ThisMonth,
+    ThisYear,
+    /// Whole days from `from` to `to`, both included (`YYYY-MM-DD`)
+    Between { from: String, to: String },
+}
+
+impl FilterNode {
+    /// The simple `col op val` filters, all joined by AND.
+    pub fn from_filters(filters: &[Filter]) -> FilterNode {
+        let children = filters
+            .iter()
+            .map(|f| {
+                let op = match f.op.as_str() {
+                    "!=" => FilterOp::Ne,
+                    ">" => FilterOp::Gt,
+                    ">=" => FilterOp::Gte,
+                    "<" => FilterOp::Lt,
+                    "<=" => FilterOp::Lte,
+                    "LIKE" => FilterOp::Like,
+                    "NOT LIKE" => FilterOp::NotLike,
+                    _ => FilterOp::Eq, // Fallback default
+                };
+                FilterNode::Condition(FilterCondition {
+                    column: f.col.clone(),
+                    expr: None,
+                    op,
+                    value: Some(serde_json::Value::String(f.val.clone())),
+                    values: Vec::new(),
+                    like_mode: None,
+                    date_range: None,
+                })
+            })
+            .collect();
+        FilterNode::Group { combinator: Combinator::And, negated: false, children }
+    }
+
+    /// Compiles the filter into a condition for `WHERE` and its parameters, in placeholder
+    /// order. Column names must be columns of the table.
+    pub fn compile(&self, columns: &[ColumnInfo]) -> Result<(String, Vec<Value>), String> {
+        let mut params = Vec::new();
+        let sql = self.compile_into(columns, &mut params)?;
+        Ok((sql, params))
+    }
+
+    fn compile_into(&self, columns: &[ColumnInfo], params: &mut Vec<Value>) -> Result<String, String> {
+        match self {
+            FilterNode::Group { combinator, negated, children } => {
+                let parts = children
+                    .iter()
+                    .map(|child| child.compile_into(columns, params))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let sql = match (combinator, parts.is_empty()) {
+                    (Combinator::And, true) => "TRUE".to_string(),
+                    (Combinator::Or, true) => "FALSE".to_string(),
+                    (Combinator::And, false) => format!("({})", parts.join(" AND ")),
+                    (Combinator::Or, false) => format!("({})", parts.join(" OR ")),
+                };
+                Ok(if *negated { format!("NOT {}", sql) } else { sql })
+            }
+            FilterNode::Condition(condition) => condition.compile_into(columns, params),
+        }
+    }
+}
+
+impl FilterCondition {
+    fn compile_into(&self, columns: &[ColumnInfo], params: &mut Vec<Value>) -> Result<String, String> {
+        let column = find_column(columns, &self.column)?;
+
+        // Equality with NULL is never true; treat it as the IS [NOT] NULL the user meant.
+        // A JSON `null` value deserialises to `None`, so both mean NULL here.
+        let op = match (self.op, &self.value) {
+            (FilterOp::Eq, None | Some(serde_json::Value::Null)) => FilterOp::IsNull,
+            (FilterOp::Ne, None | Some(serde_json::Value::Null)) => FilterOp::IsNotNull,
+            (op, _) => op,
+        };
+
+        let sql = match op {
+            FilterOp::IsNull => format!("{} IS NULL", self.lhs(column, params)?),
+            FilterOp::IsNotNull => format!("{} IS NOT NULL", self.lhs(column, params)?),
+            FilterOp::Eq | FilterOp::Ne | FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte => {
+                let sql_op = match op {
+                    FilterOp::Eq => "=",
+                    FilterOp::Ne => "!=",
+                    FilterOp::Gt => ">",
+                    FilterOp::Gte => ">=",
+                    FilterOp::Lt => "<",
+                    _ => "<=",
+                };
+                let lhs = self.lhs(column, params)?;
+                let rhs = self.operand(self.value()?, column, params)?;
+                format!("{} {} {}", lhs, sql_op, rhs)
+            }
+            FilterOp::Like | FilterOp::NotLike => {
+                let lhs = self.lhs(column, params)?;
+                let text = text_value(self.value()?);
+                let mode = self.like_mode.unwrap_or(LikeMode::Pattern);
+                let pattern = match mode {
+                    LikeMode::Pattern => text,
+                    LikeMode::Contains => format!("%{}%", escape_like(&text)),
+                    LikeMode::StartsWith => format!("{}%", escape_like(&text)),
+                    LikeMode::EndsWith => format!("%{}", escape_like(&text)),
+                    LikeMode::Exact => escape_like(&text),
+                };
+                params.push(Value::Bytes(pattern.into_bytes()));
+                let not = if op == FilterOp::NotLike { "NOT " } else { "" };
+                if mode == LikeMode::Pattern {
+                    format!("{} {}LIKE ?", lhs, not)
+                } else {
+                    format!("{} {}LIKE ? ESCAPE '{}'", lhs, not, LIKE_ESCAPE)
+                }
+            }
+            FilterOp::Regexp | FilterOp::NotRegexp => {
+                let lhs = self.lhs(column, params)?;
+                params.push(Value::Bytes(text_value(self.value()?).into_bytes()));
+                let not = if op == FilterOp::NotRegexp { "NOT " } else { "" };
+                format!("{} {}REGEXP ?", lhs, not)
+            }
+            FilterOp::In | FilterOp::NotIn => {
+                let not_in = op == FilterOp::NotIn;
+                if self.values.is_empty() {
+                    // Nothing is in an empty list
+                    return Ok(if not_in { "TRUE" } else { "FALSE" }.to_string());
+                }
+                let lhs = self.lhs(column, params)?;
+                let placeholders = self
+                    .values
+                    .iter()
+                    .map(|v| self.operand(v, column, params))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("{} {}IN ({})", lhs, if not_in { "NOT " } else { "" }, placeholders.join(", "))
+            }
+            FilterOp::Between | FilterOp::NotBetween => {
+                let [low, high] = self.values.as_slice() else {
+                    return Err(format!("BETWEEN on `{}` needs exactly two values", column.field));
+                };
+                let lhs = self.lhs(column, params)?;
+                let low = self.operand(low, column, params)?;
+                let high = self.operand(high, column, params)?;
+                let not = if op == FilterOp::NotBetween { "NOT " } else { "" };
+                format!("{} {}BETWEEN {} AND {}", lhs, not, low, high)
+            }
+            FilterOp::DateRange => {
+                let range = self
+                    .date_range
+                    .as_ref()
+                    .ok_or_else(|| format!("Date range filter on `{}` has no range", column.field))?;
+                // Parameters follow the text: the first lhs, its bounds, then the second lhs
+                let first = self.lhs(column, params)?;
+                let (start, end) = match range {
+                    DateRange::Today => ("CURDATE()", "CURDATE() + INTERVAL 1 DAY"),
+                    DateRange::Yesterday => ("CURDATE() - INTERVAL 1 DAY", "CURDATE()"),
+                    DateRange::LastDays { days } => {
+                        params.push(Value::UInt(days.saturating_sub(1) as u64));
+                        ("CURDATE() - INTERVAL ? DAY", "CURDATE() + INTERVAL 1 DAY")
+                    }
+                    DateRange::ThisWeek => (
+                        "CURDATE() - INTERVAL WEEKDAY(CURDATE()) DAY",
+                        "CURDATE() - INTERVAL WEEKDAY(CURDATE()) DAY + INTERVAL 7 DAY",
+                    ),
+                    DateRange::ThisMonth => (
+                        "CAST(DATE_FORMAT(CURDATE(), '%Y-%m-01') AS DATE)",
+                        "CAST(DATE_FORMAT(CURDATE(), '%Y-%m-01') AS DATE) + INTERVAL 1 MONTH",
+                    ),
+                    DateRange::ThisYear => (
+                        "CAST(DATE_FORMAT(CURDATE(), '%Y-01-01') AS DATE)",
+                        "CAST(DATE_FORMAT(CURDATE(), '%Y-01-01') AS DATE) + INTERVAL 1 YEAR",
+                    ),
+                    DateRange::Between { from, .. } => {
+                        params.push(Value::Bytes(from.trim().as_bytes().to_vec()));
+                        ("CAST(? AS DATE)", "CAST(? AS DATE) + INTERVAL 1 DAY")
+                    }
+                };
+                let second = self.lhs(column, params)?;
+                if let DateRange::Between { to, .. } = range {
+                    params.push(Value::Bytes(to.trim().as_bytes().to_vec()));
+                }
+                format!("({} >= {} AND {} < {})", first, start, second, end)
+            }
+        };
+        Ok(sql)
+    }
+
+    /// The filtered column, wrapped in its expression. Pushes the expression's parameters.
+    fn lhs(&self, column: &ColumnInfo, params: &mut Vec<Value>) -> Result<String, String> {
+        let ident = quote_ident(&column.field);
+        Ok(match &self.expr {
+            None => ident,
+            Some(ColumnExpr::JsonPath { path }) => {
+                if !path.trim_start().starts_with('$') {
+                    return Err(format!("Invalid JSON path for `{}`: {}", column.field, path));
+                }
+                params.push(Value::Bytes(path.trim().as_bytes().to_vec()));
+                format!("JSON_UNQUOTE(JSON_EXTRACT({}, ?))", ident)
+            }
+            Some(ColumnExpr::Lower) => format!("LOWER({})", ident),
+            Some(ColumnExpr::Upper) => format!("UPPER({})", ident),
+            Some(ColumnExpr::Trim) => format!("TRIM({})", ident),
+            Some(ColumnExpr::Length) => format!("CHAR_LENGTH({})", ident),
+            Some(ColumnExpr::Date) => format!("DATE({})", ident),
+            Some(ColumnExpr::Year) => format!("YEAR({})", ident),
+            Some(ColumnExpr::Month) => format!("MONTH({})", ident),
+        })
+    }
+
+    /// Placeholder for one comparison operand. Plain columns convert the value by column
+    /// type; expressions have no column type, so the value is sent as it came.
+    fn operand(&self, val: &serde_json::Value, column: &ColumnInfo, params: &mut Vec<Value>) -> Result<&'static str, String> {
+        if self.expr.is_some() {
+            params.push(plain_value(val));
+            return Ok("?");
+        }
+        params.push(to_column_value(val, column)?);
+        Ok(placeholder(column))
+    }
+
+    fn value(&self) -> Result<&serde_json::Value, String> {
+        self.value
+            .as_ref()
+            .ok_or_else(|| format!("Filter on `{}` has no value", self.column))
+    }
+}
+
+fn plain_value(val: &serde_json::Value) -> Value {
+    match val {
+        serde_json::Value::Null => Value::NULL,
+        serde_json::Value::Bool(b) => Value::Int(*b as i64),
+        serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => Value::Int(i),
+            (None, Some(u)) => Value::UInt(u),
+            _ => Value::Double(n.as_f64().unwrap_or(0.0)),
+        },
+        serde_json::Value::String(s) => Value::Bytes(s.clone().into_bytes()),
+        other => Value::Bytes(other.to_string().into_bytes()),
+    }
+}
+
+fn text_value(val: &serde_json::Value) -> String {
+    match val {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == LIKE_ESCAPE || c == '%' || c == '_' {
+            escaped.push(LIKE_ESCAPE);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::column;
+    use serde_json::json;
+
+    fn columns() -> Vec<ColumnInfo> {
+        vec![column("id", "int(11)"), column("name", "varchar(50)"), column("created", "datetime"), column("doc", "json")]
+    }
+
+    fn compile(filter: serde_json::Value) -> Result<(String, Vec<Value>), String> {
+        serde_json::from_value::<FilterNode>(filter).unwrap().compile(&columns())
+    }
+
+    fn text(s: &str) -> Value {
+        Value::Bytes(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn compiles_nested_groups() {
+        let (sql, params) = compile(json!({
+            "type": "group", "combinator": "and", "children": [
+                {"type": "condition", "column": "id", "op": "gt", "value": 5},
+                {"type": "group", "combinator": "or", "negated": true, "children": [
+                    {"type": "condition", "column": "name", "op": "eq", "value": "a"},
+                    {"type": "group", "combinator": "and", "children": [
+                        {"type": "condition", "column": "name", "op": "is_null"},
+                        {"type": "condition", "column": "id", "op": "ne", "value": null}
+                    ]}
+                ]}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(sql, "(`id` > ? AND NOT (`name` = ? OR (`name` IS NULL AND `id` IS NOT NULL)))");
+        assert_eq!(params, vec![Value::Int(5), text("a")]);
+    }
+
+    #[test]
+    fn compiles_empty_groups_and_lists() {
+        assert_eq!(compile(json!({"type": "group", "combinator": "and", "children": []})).unwrap().0, "TRUE");
+        assert_eq!(compile(json!({"type": "group", "combinator": "or", "children": []})).unwrap().0, "FALSE");
+        assert_eq!(compile(json!({"type": "group", "combinator": "or", "negated": true, "children": []})).unwrap().0, "NOT FALSE");
+
+        let (sql, params) = compile(json!({"type": "condition", "column": "id", "op": "in", "values": []})).unwrap();
+        assert_eq!(sql, "FALSE");
+        assert!(params.is_empty());
+        assert_eq!(compile(json!({"type": "condition", "column": "id", "op": "not_in"})).unwrap().0, "TRUE");
+
+        let (sql, params) = compile(json!({"type": "condition", "column": "id", "op": "not_in", "values": [1, "2"]})).unwrap();
+        assert_eq!(sql, "`id` NOT IN (?, ?)");
+        assert_eq!(params, vec![Value::Int(1), Value::Int(2)]);
+    }
+
+    #[test]
+    fn escapes_like_values_by_mode() {
+        let like = |mode: Option<&str>, op: &str| {
+            let mut filter = json!({"type": "condition", "column": "name", "op": op, "value": "5%_a!b"});
+            if let Some(mode) = mode {
+                filter["like_mode"] = json!(mode);
+            }
+            compile(filter).unwrap()
+        };
+
+        assert_eq!(like(None, "like"), ("`name` LIKE ?".to_string(), vec![text("5%_a!b")]));
+        assert_eq!(like(Some("pattern"), "not_like"), ("`name` NOT LIKE ?".to_string(), vec![text("5%_a!b")]));
+        assert_eq!(like(Some("contains"), "like"), ("`name` LIKE ? ESCAPE '!'".to_string(), vec![text("%5!%!_a!!b%")]));
+        assert_eq!(like(Some("starts_with"), "like").1, vec![text("5!%!_a!!b%")]);
+        assert_eq!(like(Some("ends_with"), "like").1, vec![text("%5!%!_a!!b")]);
+        assert_eq!(like(Some("exact"), "not_like"), ("`name` NOT LIKE ? ESCAPE '!'".to_string(), vec![text("5!%!_a!!b")]));
+    }
+
+    #[test]
+    fn between_needs_two_values() {
+        let (sql, params) = compile(json!({"type": "condition", "column": "id", "op": "not_between", "values": [1, 9]})).unwrap();
+        assert_eq!(sql, "`id` NOT BETWEEN ? AND ?");
+        assert_eq!(params, vec![Value::Int(1), Value::Int(9)]);
+
+        for values in [json!([]), json!([1]), json!([1, 2, 3])] {
+            let err = compile(json!({"type": "condition", "column": "id", "op": "between", "values": values})).unwrap_err();
+            assert_eq!(err, "BETWEEN on `id` needs exactly two values");
+        }
+    }
+
+    #[test]
+    fn orders_date_range_parameters_as_written() {
+        let (sql, params) = compile(json!({
+            "type": "condition", "column": "created", "op": "date_range",
+            "date_range": {"kind": "between", "from": "2024-01-01", "to": " 2024-01-31 "}
+        }))
+        .unwrap();
+        assert_eq!(sql, "(`created` >= CAST(? AS DATE) AND `created` < CAST(? AS DATE) + INTERVAL 1 DAY)");
+        assert_eq!(params, vec![text("2024-01-01"), text("2024-01-31")]);
+
+        let (sql, params) = compile(json!({
+            "type": "condition", "column": "created", "op": "date_range", "date_range": {"kind": "last_days", "days": 7}
+        }))
+        .unwrap();
+        assert_eq!(sql, "(`created` >= CURDATE() - INTERVAL ? DAY AND `created` < CURDATE() + INTERVAL 1 DAY)");
+        assert_eq!(params, vec![Value::UInt(6)]);
+
+        // Each copy of the JSON path expression brings its own parameter
+        let (sql, params) = compile(json!({
+            "type": "condition", "column": "doc", "expr": {"kind": "json_path", "path": "$.at"}, "op": "date_range",
+            "date_range": {"kind": "between", "from": "2024-01-01", "to": "2024-01-31"}
+        }))
+        .unwrap();
+        assert_eq!(
+            sql,
+            "(JSON_UNQUOTE(JSON_EXTRACT(`doc`, ?)) >= CAST(? AS DATE) AND JSON_UNQUOTE(JSON_EXTRACT(`doc`, ?)) < CAST(? AS DATE) + INTERVAL 1 DAY)"
+        );
+        assert_eq!(params, vec![text("$.at"), text("2024-01-01"), text("$.at"), text("2024-01-31")]);
+
+        let (_, params) = compile(json!({
+            "type": "condition", "column": "doc", "expr": {"kind": "json_path", "path": "$.at"}, "op": "date_range",
+            "date_range": {"kind": "last_days", "days": 0}
+        }))
+        .unwrap();
+        assert_eq!(params, vec![text("$.at"), Value::UInt(0), text("$.at")]);
+
+        assert!(compile(json!({"type": "condition", "column": "created", "op": "date_range"})).is_err());
+    }
+
+    #[test]
+    fn compares_expressions_with_plain_values() {
+        let (sql, params) = compile(json!({
+            "type": "condition", "column": "doc", "expr": {"kind": "json_path", "path": "$.city"}, "op": "eq", "value": "Oslo"
+        }))
+        .unwrap();
+        assert_eq!(sql, "JSON_UNQUOTE(JSON_EXTRACT(`doc`, ?)) = ?");
+        assert_eq!(params, vec![text("$.city"), text("Oslo")]);
+
+        let (sql, params) = compile(json!({"type": "condition", "column": "doc", "op": "eq", "value": {"a": 1}})).unwrap();
+        assert_eq!(sql, "`doc` = CAST(? AS JSON)");
+        assert_eq!(params, vec![text(r#"{"a":1}"#)]);
+
+        let bad_path = json!({"type": "condition", "column": "doc", "expr": {"kind": "json_path", "path": "city"}, "op": "is_null"});
+        assert!(compile(bad_path).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_columns() {
+        let err = compile(json!({"type": "condition", "column": "id`; DROP TABLE t; --", "op": "eq", "value": 1})).unwrap_err();
+        assert!(err.starts_with("Unknown column"), "{}", err);
+
+        let nested = json!({"type": "group", "combinator": "or", "children": [
+            {"type": "condition", "column": "id", "op": "eq", "value": 1},
+            {"type": "condition", "column": "missing", "op": "is_null"}
+        ]});
+        assert_eq!(compile(nested).unwrap_err(), "Unknown column `missing`");
+
+        // Column names match case-insensitively and are emitted as the table spells them
+        assert_eq!(compile(json!({"type": "condition", "column": "NAME", "op": "is_null"})).unwrap().0, "`name` IS NULL");
+    }
+}
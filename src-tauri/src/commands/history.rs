@@ -0,0 +1,79 @@
+use tauri::{AppHandle, Manager, State};
+use crate::state::AppState;
+use crate::query_history::{HistoryEntry, HistoryFilter, HistoryRetention, QueryHistory};
+use crate::commands::preferences::{load_preferences, write_preferences};
+
+/// Runs `f` on the history store, opening it on first use. Opening also moves any history
+/// still stored in preferences.json into the store.
+pub(crate) fn with_history<R>(
+    app_handle: &AppHandle,
+    state: &AppState,
+    f: impl FnOnce(&mut QueryHistory) -> Result<R, String>,
+) -> Result<R, String> {
+    let mut guard = state.history.lock().unwrap();
+    if guard.is_none() {
+        let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+
+        let mut prefs = load_preferences(app_handle.clone())?;
+        let mut history = QueryHistory::open(&config_dir, prefs.history_retention.clone().unwrap_or_default())?;
+
+        if !prefs.query_history.is_empty() {
+            let legacy = prefs.query_history.drain(..).map(|item| HistoryEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                sql: item.sql,
+                timestamp: item.timestamp,
+                server_id: None,
+                database: None,
+                duration_ms: 0.0,
+                rows_affected: 0,
+                rows_returned: 0,
+                success: true,
+                error: None,
+                pinned: false,
+            });
+            history.import(legacy.collect())?;
+            // An empty query_history is not serialized, so this drops it from the file. If the
+            // write fails the next open imports the list again, which skips what is already in.
+            write_preferences(app_handle, &prefs)?;
+        }
+
+        *guard = Some(history);
+    }
+    f(guard.as_mut().unwrap())
+}
+
+#[tauri::command]
+pub fn search_query_history(filter: Option<HistoryFilter>, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<HistoryEntry>, String> {
+    let filter = filter.unwrap_or_default();
+    with_history(&app_handle, &state, |history| Ok(history.search(&filter)))
+}
+
+#[tauri::command]
+pub fn pin_query_history(id: String, pinned: bool, app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    with_history(&app_handle, &state, |history| history.set_pinned(&id, pinned))
+}
+
+#[tauri::command]
+pub fn delete_query_history(id: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    with_history(&app_handle, &state, |history| history.delete(&id))
+}
+
+/// Removes every unpinned entry, or only those of `server_id`.
+#[tauri::command]
+pub fn clear_query_history(server_id: Option<String>, app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    with_history(&app_handle, &state, |history| history.clear(server_id.as_deref()))
+}
+
+#[tauri::command]
+pub fn get_history_retention(app_handle: AppHandle, state: State<'_, AppState>) -> Result<HistoryRetention, String> {
+    with_history(&app_handle, &state, |history| Ok(history.retention().clone()))
+}
+
+#[tauri::command]
+pub fn set_history_retention(retention: HistoryRetention, app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    with_history(&app_handle, &state, |history| history.set_retention(retention.clone()))?;
+    let mut prefs = load_preferences(app_handle.clone())?;
+    prefs.history_retention = Some(retention);
+    write_preferences(&app_handle, &prefs)
+}
@@ -14,11 +14,8 @@ pub struct IndexInfo {
 }
 
 #[tauri::command]
-pub async fn get_indexes(db: String, table: String, state: State<'_, AppState>) -> Result<Vec<IndexInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_indexes(db: String, table: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<IndexInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     conn.query_drop(format!("USE `{}`", db)).await.map_err(|e| e.to_string())?;
@@ -43,11 +40,8 @@ pub async fn get_indexes(db: String, table: String, state: State<'_, AppState>)
 }
 
 #[tauri::command]
-pub async fn add_index(db: String, table: String, index_name: String, columns: Vec<String>, index_type: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn add_index(db: String, table: String, index_name: String, columns: Vec<String>, index_type: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     // index_type: UNIQUE, FULLTEXT, SPATIAL, or empty for normal (INDEX)
@@ -78,11 +72,8 @@ pub async fn add_index(db: String, table: String, index_name: String, columns: V
 }
 
 #[tauri::command]
-pub async fn drop_index(db: String, table: String, name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_index(db: String, table: String, name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let query = if name == "PRIMARY" {
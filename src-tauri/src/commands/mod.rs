@@ -1,7 +1,14 @@
 pub mod database;
 pub mod table;
+pub mod changeset;
+pub mod filter;
 pub mod query;
+pub mod transaction;
+pub mod explain;
+pub mod profiling;
+pub mod row_values;
 pub mod server;
+pub mod credentials;
 pub mod import_export;
 pub mod relations;
 pub mod common;
@@ -13,5 +20,8 @@ pub mod routines;
 pub mod snippets;
 pub mod ai;
 pub mod preferences;
+pub mod history;
 pub mod monitor;
 pub mod debug_logger;
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -12,11 +12,8 @@ pub struct ServerStatus {
 }
 
 #[tauri::command]
-pub async fn get_server_status(state: State<'_, AppState>) -> Result<ServerStatus, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_server_status(session_id: Option<String>, state: State<'_, AppState>) -> Result<ServerStatus, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let query = "SHOW GLOBAL STATUS WHERE Variable_name IN ('Threads_connected', 'Bytes_received', 'Bytes_sent', 'Questions')";
@@ -1,5 +1,8 @@
 use tauri::command;
-use tauri::Manager;
+use tauri::{Manager, State};
+use crate::commands::history::with_history;
+use crate::query_history::HistoryRetention;
+use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -12,7 +15,13 @@ pub struct AppPreferences {
     pub font_family: String,
     pub dashboard_view_mode: String,
     pub show_system_dbs: bool,
+    /// Legacy: history now lives in query_history.jsonl. Kept on disk only until
+    /// `with_history` has imported it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub query_history: Vec<QueryHistoryItem>,
+    /// `None` from the frontend keeps the stored value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_retention: Option<HistoryRetention>,
     // Performance & Debug Settings
     pub debug_mode: bool,
     pub performance_monitoring: bool,
@@ -72,6 +81,7 @@ impl Default for AppPreferences {
             dashboard_view_mode: "grid".to_string(),
             show_system_dbs: false,
             query_history: vec![],
+            history_retention: None,
             // Performance defaults
             debug_mode: false,
             performance_monitoring: false,
@@ -107,10 +117,32 @@ pub fn load_preferences(app_handle: tauri::AppHandle) -> Result<AppPreferences,
 #[command]
 pub fn save_preferences(
     app_handle: tauri::AppHandle, 
-    preferences: AppPreferences
+    preferences: AppPreferences,
+    state: State<'_, AppState>
 ) -> Result<(), String> {
-    let path = get_prefs_path(&app_handle)?;
-    let content = serde_json::to_string_pretty(&preferences).map_err(|e| e.to_string())?;
+    // Move any legacy history into its store before the file is rewritten. If that fails the
+    // legacy entries are carried over below and the import is retried next time.
+    if let Err(e) = with_history(&app_handle, &state, |_| Ok(())) {
+        log::warn!("Query history migration failed: {}", e);
+    }
+
+    let mut preferences = preferences;
+    let stored = load_preferences(app_handle.clone())?;
+    preferences.query_history = stored.query_history;
+    match &preferences.history_retention {
+        Some(retention) => {
+            if let Some(history) = state.history.lock().unwrap().as_mut() {
+                history.set_retention(retention.clone())?;
+            }
+        }
+        None => preferences.history_retention = stored.history_retention,
+    }
+    write_preferences(&app_handle, &preferences)
+}
+
+pub(crate) fn write_preferences(app_handle: &tauri::AppHandle, preferences: &AppPreferences) -> Result<(), String> {
+    let path = get_prefs_path(app_handle)?;
+    let content = serde_json::to_string_pretty(preferences).map_err(|e| e.to_string())?;
     fs::write(path, content).map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -0,0 +1,172 @@
+use mysql_async::prelude::*;
+use mysql_async::Conn;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Session counters worth diffing around a statement
+const STATUS_FILTER: &str = "SHOW SESSION STATUS WHERE Variable_name LIKE 'Handler\\_%' \
+    OR Variable_name LIKE 'Created\\_tmp%' OR Variable_name LIKE 'Sort\\_%' OR Variable_name LIKE 'Select\\_%'";
+
+/// performance_schema timers are in picoseconds
+const PICOS_PER_MS: f64 = 1_000_000_000.0;
+
+#[derive(Serialize, Default, Debug)]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration_ms: f64,
+}
+
+/// Execution statistics of the profiled call. Statement counters are summed over every
+/// statement of the call; they are `None` when performance_schema is unavailable.
+#[derive(Serialize, Default, Debug)]
+pub struct QueryProfile {
+    pub source: String, // "performance_schema" | "session_status"
+    pub server_time_ms: Option<f64>,
+    pub lock_time_ms: Option<f64>,
+    pub rows_examined: Option<u64>,
+    pub rows_sent: Option<u64>,
+    pub created_tmp_tables: Option<u64>,
+    pub created_tmp_disk_tables: Option<u64>,
+    pub sort_merge_passes: Option<u64>,
+    pub select_full_join: Option<u64>,
+    pub select_scan: Option<u64>,
+    pub no_index_used: Option<u64>,
+    /// Empty unless the events_stages_history_long consumer is enabled
+    pub stages: Vec<StageTiming>,
+    /// More statements ran than performance_schema keeps per thread
+    /// (`performance_schema_events_statements_history_size`, 10 by default); the statement
+    /// counters only cover the most recent ones
+    pub truncated: bool,
+    /// Non-zero changes of Handler_*, Created_tmp_*, Sort_* and Select_* counters.
+    /// Includes the small overhead of the SHOW STATUS call itself.
+    pub status_deltas: BTreeMap<String, u64>,
+}
+
+/// Snapshots taken before the profiled statement runs.
+pub struct Profiler {
+    thread_id: Option<u64>,
+    /// Event id of a statement issued right before the profiled ones
+    marker: Option<u64>,
+    status_before: HashMap<String, u64>,
+}
+
+impl Profiler {
+    pub async fn start(conn: &mut Conn) -> Result<Self, String> {
+        let status_before = session_status(conn).await?;
+
+        // performance_schema may be disabled or inaccessible; fall back to status deltas
+        let thread_id: Option<u64> = conn
+            .query_first("SELECT THREAD_ID FROM performance_schema.threads WHERE PROCESSLIST_ID = CONNECTION_ID()")
+            .await
+            .unwrap_or(None);
+        let marker = match thread_id {
+            Some(id) => current_event_id(conn, id).await,
+            None => None,
+        };
+
+        Ok(Profiler { thread_id, marker, status_before })
+    }
+
+    pub async fn finish(self, conn: &mut Conn) -> Result<QueryProfile, String> {
+        // Closes the range of profiled statements before anything else runs
+        let end_marker = match self.thread_id {
+            Some(id) => current_event_id(conn, id).await,
+            None => None,
+        };
+        let status_after = session_status(conn).await?;
+        let status_deltas = status_after
+            .iter()
+            .filter_map(|(name, after)| {
+                let delta = after.saturating_sub(*self.status_before.get(name).unwrap_or(&0));
+                (delta > 0).then(|| (name.clone(), delta))
+            })
+            .collect();
+
+        let mut profile = QueryProfile {
+            source: "session_status".to_string(),
+            status_deltas,
+            ..Default::default()
+        };
+
+        if let (Some(thread_id), Some(marker), Some(end_marker)) = (self.thread_id, self.marker, end_marker) {
+            // Ignore failures: the statement itself already succeeded
+            let _ = fill_from_performance_schema(conn, thread_id, marker, end_marker, &mut profile).await;
+        }
+        Ok(profile)
+    }
+}
+
+/// Event id of the statement asking for it. Plain text protocol: a prepared statement would
+/// add its own `Prepare` event to the history.
+async fn current_event_id(conn: &mut Conn, thread_id: u64) -> Option<u64> {
+    conn.query_first(format!(
+        "SELECT EVENT_ID FROM performance_schema.events_statements_current WHERE THREAD_ID = {}",
+        thread_id
+    ))
+    .await
+    .unwrap_or(None)
+}
+
+async fn session_status(conn: &mut Conn) -> Result<HashMap<String, u64>, String> {
+    let rows: Vec<(String, String)> = conn.query(STATUS_FILTER).await.map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().filter_map(|(k, v)| v.parse().ok().map(|v| (k, v))).collect())
+}
+
+async fn fill_from_performance_schema(conn: &mut Conn, thread_id: u64, marker: u64, end_marker: u64, profile: &mut QueryProfile) -> Result<(), mysql_async::Error> {
+    // The statements strictly between the two markers are the profiled ones
+    let mut statements: Vec<mysql_async::Row> = conn
+        .query(format!(
+            "SELECT EVENT_ID, TIMER_WAIT, LOCK_TIME, ROWS_EXAMINED, ROWS_SENT, CREATED_TMP_TABLES, \
+             CREATED_TMP_DISK_TABLES, SORT_MERGE_PASSES, SELECT_FULL_JOIN, SELECT_SCAN, NO_INDEX_USED \
+             FROM performance_schema.events_statements_history \
+             WHERE THREAD_ID = {} AND EVENT_ID >= {} AND EVENT_ID < {} ORDER BY EVENT_ID",
+            thread_id, marker, end_marker
+        ))
+        .await?;
+    // The history is a ring buffer; once the start marker has rolled out, so have the
+    // earliest profiled statements
+    match statements.first().and_then(|r| r.get::<u64, _>("EVENT_ID")) {
+        Some(id) if id == marker => {
+            statements.remove(0);
+        }
+        _ => profile.truncated = true,
+    }
+    if statements.is_empty() {
+        return Ok(());
+    }
+
+    let sum = |col: &str| -> u64 { statements.iter().map(|r| r.get::<Option<u64>, _>(col).flatten().unwrap_or(0)).sum() };
+    profile.source = "performance_schema".to_string();
+    profile.server_time_ms = Some(sum("TIMER_WAIT") as f64 / PICOS_PER_MS);
+    profile.lock_time_ms = Some(sum("LOCK_TIME") as f64 / PICOS_PER_MS);
+    profile.rows_examined = Some(sum("ROWS_EXAMINED"));
+    profile.rows_sent = Some(sum("ROWS_SENT"));
+    profile.created_tmp_tables = Some(sum("CREATED_TMP_TABLES"));
+    profile.created_tmp_disk_tables = Some(sum("CREATED_TMP_DISK_TABLES"));
+    profile.sort_merge_passes = Some(sum("SORT_MERGE_PASSES"));
+    profile.select_full_join = Some(sum("SELECT_FULL_JOIN"));
+    profile.select_scan = Some(sum("SELECT_SCAN"));
+    profile.no_index_used = Some(sum("NO_INDEX_USED"));
+
+    let ids: Vec<String> = statements
+        .iter()
+        .filter_map(|r| r.get::<u64, _>("EVENT_ID"))
+        .map(|id| id.to_string())
+        .collect();
+    let stages: Vec<(String, Option<u64>)> = conn
+        .query(format!(
+            "SELECT EVENT_NAME, TIMER_WAIT FROM performance_schema.events_stages_history_long \
+             WHERE THREAD_ID = {} AND NESTING_EVENT_ID IN ({}) ORDER BY EVENT_ID",
+            thread_id,
+            ids.join(",")
+        ))
+        .await?;
+    profile.stages = stages
+        .into_iter()
+        .map(|(name, wait)| StageTiming {
+            stage: name.trim_start_matches("stage/sql/").to_string(),
+            duration_ms: wait.unwrap_or(0) as f64 / PICOS_PER_MS,
+        })
+        .collect();
+    Ok(())
+}
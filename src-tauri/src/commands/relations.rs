@@ -15,11 +15,8 @@ pub struct ForeignKeyRel {
 }
 
 #[tauri::command]
-pub async fn get_foreign_keys(db: String, table: String, state: State<'_, AppState>) -> Result<Vec<ForeignKeyRel>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_foreign_keys(db: String, table: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<ForeignKeyRel>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     conn.query_drop(format!("USE `{}`", db)).await.map_err(|e| e.to_string())?;
@@ -75,12 +72,10 @@ pub async fn add_foreign_key(
     ref_column: String, 
     on_delete: String, 
     on_update: String,
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let constraint_name = if let Some(n) = name {
@@ -98,11 +93,8 @@ pub async fn add_foreign_key(
 }
 
 #[tauri::command]
-pub async fn drop_foreign_key(db: String, table: String, name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_foreign_key(db: String, table: String, name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let sql = format!("ALTER TABLE `{}`.`{}` DROP FOREIGN KEY `{}`", db, table, name);
@@ -14,11 +14,8 @@ pub struct Routine {
 }
 
 #[tauri::command]
-pub async fn get_routines(db: String, state: State<'_, AppState>) -> Result<Vec<Routine>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_routines(db: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<Routine>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let sql = format!(
@@ -46,11 +43,8 @@ pub async fn get_routines(db: String, state: State<'_, AppState>) -> Result<Vec<
 }
 
 #[tauri::command]
-pub async fn get_routine_definition(db: String, name: String, routine_type: String, state: State<'_, AppState>) -> Result<String, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_routine_definition(db: String, name: String, routine_type: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<String, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let sql = if routine_type.to_uppercase() == "PROCEDURE" {
@@ -69,11 +63,8 @@ pub async fn get_routine_definition(db: String, name: String, routine_type: Stri
 }
 
 #[tauri::command]
-pub async fn drop_routine(db: String, name: String, routine_type: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_routine(db: String, name: String, routine_type: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let sql = format!("DROP {} `{}`.`{}`", routine_type, db, name);
@@ -86,12 +77,10 @@ pub async fn save_routine(
     old_name: String,
     routine_type: String,
     sql: String,
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     // Start a transaction would be ideal, but for now we'll do it sequentially.
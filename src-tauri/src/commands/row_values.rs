@@ -0,0 +1,403 @@
+use crate::commands::table::ColumnInfo;
+use mysql_async::consts::ColumnType;
+use mysql_async::{Column, Value};
+
+/// Prefix `common::mysql_to_json` puts in front of base64-encoded binary values
+pub const BINARY_PREFIX: &str = "_binary_base64:";
+
+/// Lowercased type name without length, precision or attributes: `int(10) unsigned` -> `int`.
+pub fn base_type(data_type: &str) -> String {
+    data_type
+        .split(|c: char| c == '(' || c.is_whitespace())
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// Converts a grid value into a statement parameter for `column`, using the same
+/// representation the server would accept for that type.
+pub fn to_column_value(val: &serde_json::Value, column: &ColumnInfo) -> Result<Value, String> {
+    use serde_json::Value as Json;
+
+    let invalid = |what: &str| format!("Invalid {} for column `{}`: {}", what, column.field, val);
+
+    if val.is_null() {
+        return Ok(Value::NULL);
+    }
+    if base_type(&column.data_type) == "bit" {
+        // Taken as a number whatever the form, so the bytes are never left for the server to guess
+        let width = bit_width(&column.data_type);
+        let bits = match val {
+            Json::Bool(b) => Some(*b as u64),
+            Json::Number(n) => n.as_u64(),
+            Json::String(s) => match s.strip_prefix(BINARY_PREFIX) {
+                Some(encoded) => binary_bits(encoded, width),
+                None => bit_value(s.trim()),
+            },
+            _ => None,
+        };
+        return bits
+            .filter(|&bits| width >= 64 || bits >> width == 0)
+            .map(Value::UInt)
+            .ok_or_else(|| invalid("bit value"));
+    }
+    if let Some(encoded) = val.as_str().and_then(|s| s.strip_prefix(BINARY_PREFIX)) {
+        use base64::{engine::general_purpose, Engine as _};
+        let bytes = general_purpose::STANDARD.decode(encoded).map_err(|_| invalid("binary value"))?;
+        return Ok(Value::Bytes(bytes));
+    }
+
+    Ok(match base_type(&column.data_type).as_str() {
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "year" | "bool" | "boolean" | "serial" => {
+            integer_value(val).ok_or_else(|| invalid("integer"))?
+        }
+        "float" | "double" | "real" => match val {
+            Json::Bool(b) => Value::Double(*b as u8 as f64),
+            Json::Number(n) => Value::Double(n.as_f64().ok_or_else(|| invalid("number"))?),
+            Json::String(s) => Value::Double(s.trim().parse().map_err(|_| invalid("number"))?),
+            _ => return Err(invalid("number")),
+        },
+        // Sent as text so no precision is lost on the way
+        "decimal" | "numeric" | "dec" | "fixed" => match val {
+            Json::Bool(b) => Value::Int(*b as i64),
+            Json::Number(n) => Value::Bytes(n.to_string().into_bytes()),
+            Json::String(s) if is_decimal(s.trim()) => Value::Bytes(s.trim().as_bytes().to_vec()),
+            _ => return Err(invalid("decimal")),
+        },
+        "json" => match val {
+            Json::String(s) => Value::Bytes(s.clone().into_bytes()),
+            other => Value::Bytes(other.to_string().into_bytes()),
+        },
+        // Values the server accepts but chrono does not (zero dates) are passed through as text
+        "date" | "datetime" | "timestamp" => match val {
+            Json::String(s) => datetime_value(s.trim()).unwrap_or_else(|| Value::Bytes(s.clone().into_bytes())),
+            _ => return Err(invalid("date")),
+        },
+        "time" => match val {
+            Json::String(s) => Value::Bytes(time_text(s.trim()).into_bytes()),
+            Json::Number(n) => Value::Bytes(n.to_string().into_bytes()),
+            _ => return Err(invalid("time")),
+        },
+        _ => match val {
+            Json::String(s) => Value::Bytes(s.clone().into_bytes()),
+            Json::Bool(b) => Value::Bytes(if *b { b"1".to_vec() } else { b"0".to_vec() }),
+            other => Value::Bytes(other.to_string().into_bytes()),
+        },
+    })
+}
+
+/// Placeholder for a parameter compared against `column`. JSON columns compare as JSON
+/// documents, so the text parameter has to be cast first.
+pub fn placeholder(column: &ColumnInfo) -> &'static str {
+    if base_type(&column.data_type) == "json" {
+        "CAST(? AS JSON)"
+    } else {
+        "?"
+    }
+}
+
+/// Null-safe equality between `ident` (the quoted column) and a row key value, with its
+/// parameters. FLOAT and DOUBLE values are approximate and the text the grid shows for them
+/// need not be what the server would print, so they match within a relative tolerance.
+pub fn key_comparison(ident: &str, val: &serde_json::Value, column: &ColumnInfo) -> Result<(String, Vec<Value>), String> {
+    let tolerance = match base_type(&column.data_type).as_str() {
+        // FLOAT keeps about 7 significant digits, and older servers print only 6
+        "float" => FLOAT_TOLERANCE,
+        "double" | "real" => DOUBLE_TOLERANCE,
+        _ => return Ok((format!("{} <=> {}", ident, placeholder(column)), vec![to_column_value(val, column)?])),
+    };
+    match to_column_value(val, column)? {
+        Value::NULL => Ok((format!("{} IS NULL", ident), Vec::new())),
+        Value::Double(d) => Ok((format!("ABS({} - ?) <= ?", ident), vec![Value::Double(d), Value::Double(d.abs() * tolerance)])),
+        // Raw bytes from a binary cell
+        other => Ok((format!("{} <=> ?", ident), vec![other])),
+    }
+}
+
+const FLOAT_TOLERANCE: f64 = 1e-5;
+const DOUBLE_TOLERANCE: f64 = 1e-14;
+
+/// Renders a binary-protocol value the way the text protocol would have sent it, so the
+/// grid shows the same cells whether or not a query was prepared. BIT values, which arrive
+/// as raw bytes that may or may not look like text, become numbers.
+pub fn to_text_value(val: Value, column: &Column) -> Value {
+    let text = match val {
+        Value::Bytes(bytes) if column.column_type() == ColumnType::MYSQL_TYPE_BIT => {
+            return Value::UInt(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64));
+        }
+        Value::Int(n) => n.to_string(),
+        Value::UInt(n) => n.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Double(d) => d.to_string(),
+        Value::Date(y, m, d, h, i, s, us) => match column.column_type() {
+            ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => format!("{:04}-{:02}-{:02}", y, m, d),
+            _ => format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}{}", y, m, d, h, i, s, fraction(us, column.decimals())),
+        },
+        Value::Time(neg, d, h, i, s, us) => format!(
+            "{}{:02}:{:02}:{:02}{}",
+            if neg { "-" } else { "" },
+            d * 24 + h as u32,
+            i,
+            s,
+            fraction(us, column.decimals())
+        ),
+        other => return other,
+    };
+    Value::Bytes(text.into_bytes())
+}
+
+/// Fractional seconds with as many digits as the column declares. Computed columns report
+/// 31 decimals, in which case all six are shown when non-zero.
+fn fraction(micros: u32, decimals: u8) -> String {
+    let digits = match decimals {
+        0..=6 => decimals as usize,
+        _ if micros != 0 => 6,
+        _ => 0,
+    };
+    if digits == 0 {
+        return String::new();
+    }
+    format!(".{}", &format!("{:06}", micros)[..digits])
+}
+
+fn integer_value(val: &serde_json::Value) -> Option<Value> {
+    match val {
+        serde_json::Value::Bool(b) => Some(Value::Int(*b as i64)),
+        serde_json::Value::Number(n) => n.as_i64().map(Value::Int).or_else(|| n.as_u64().map(Value::UInt)),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            match s.to_lowercase().as_str() {
+                "true" => Some(Value::Int(1)),
+                "false" => Some(Value::Int(0)),
+                _ => s.parse().map(Value::Int).or_else(|_| s.parse().map(Value::UInt)).ok(),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Declared length of a BIT column: `bit(12)` -> 12, `bit` -> 1.
+fn bit_width(data_type: &str) -> u32 {
+    data_type
+        .split_once('(')
+        .and_then(|(_, rest)| rest.split(')').next())
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Big-endian bits of a base64-encoded BIT value, which is `ceil(width / 8)` bytes long.
+fn binary_bits(encoded: &str, width: u32) -> Option<u64> {
+    use base64::{engine::general_purpose, Engine as _};
+    let bytes = general_purpose::STANDARD.decode(encoded).ok()?;
+    if bytes.is_empty() || bytes.len() > width.div_ceil(8) as usize {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64))
+}
+
+/// `b'101'`, `0b101` or a plain decimal number.
+fn bit_value(s: &str) -> Option<u64> {
+    let lower = s.to_lowercase();
+    if let Some(bits) = lower.strip_prefix("b'").and_then(|b| b.strip_suffix('\'')) {
+        return u64::from_str_radix(bits, 2).ok();
+    }
+    if let Some(bits) = lower.strip_prefix("0b") {
+        return u64::from_str_radix(bits, 2).ok();
+    }
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        return s.parse().ok();
+    }
+    None
+}
+
+fn is_decimal(s: &str) -> bool {
+    let digits = s.strip_prefix(&['-', '+'][..]).unwrap_or(s);
+    let mut parts = digits.splitn(2, '.');
+    let int = parts.next().unwrap_or("");
+    let frac = parts.next().unwrap_or("");
+    (!int.is_empty() || !frac.is_empty())
+        && int.bytes().all(|b| b.is_ascii_digit())
+        && frac.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn datetime_value(s: &str) -> Option<Value> {
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+
+    let datetime = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))?;
+
+    Some(Value::Date(
+        datetime.year() as u16,
+        datetime.month() as u8,
+        datetime.day() as u8,
+        datetime.hour() as u8,
+        datetime.minute() as u8,
+        datetime.second() as u8,
+        datetime.nanosecond() / 1000,
+    ))
+}
+
+/// Accepts the `-1d 02:00:00` form `mysql_to_json` gives TIME values and turns it into the
+/// server's `-1 02:00:00`.
+fn time_text(s: &str) -> String {
+    match s.split_once("d ") {
+        Some((days, rest)) if !days.is_empty() && days.trim_start_matches('-').bytes().all(|b| b.is_ascii_digit()) => format!("{} {}", days, rest),
+        _ => s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::column;
+    use serde_json::json;
+
+    fn convert(val: serde_json::Value, data_type: &str) -> Result<Value, String> {
+        to_column_value(&val, &column("c", data_type))
+    }
+
+    #[test]
+    fn strips_type_attributes() {
+        assert_eq!(base_type("int(10) unsigned"), "int");
+        assert_eq!(base_type("DECIMAL(10,2)"), "decimal");
+        assert_eq!(base_type("json"), "json");
+    }
+
+    #[test]
+    fn converts_null_and_binary() {
+        assert_eq!(convert(json!(null), "int").unwrap(), Value::NULL);
+        assert_eq!(convert(json!(null), "datetime").unwrap(), Value::NULL);
+        assert_eq!(convert(json!("_binary_base64:AAH/"), "blob").unwrap(), Value::Bytes(vec![0, 1, 255]));
+        assert!(convert(json!("_binary_base64:not base64!"), "blob").is_err());
+    }
+
+    #[test]
+    fn converts_bits_and_booleans() {
+        assert_eq!(convert(json!(true), "bit(1)").unwrap(), Value::UInt(1));
+        assert_eq!(convert(json!(5), "bit(3)").unwrap(), Value::UInt(5));
+        assert_eq!(convert(json!("b'101'"), "bit(3)").unwrap(), Value::UInt(5));
+        assert_eq!(convert(json!("0b11"), "bit(2)").unwrap(), Value::UInt(3));
+        assert_eq!(convert(json!("_binary_base64:AQI="), "bit(12)").unwrap(), Value::UInt(0x102));
+        assert!(convert(json!("_binary_base64:AQI="), "bit(8)").is_err());
+        assert!(convert(json!(-1), "bit(1)").is_err());
+        assert!(convert(json!(2), "bit(1)").is_err());
+        assert!(convert(json!("\u{1}"), "bit(1)").is_err());
+        assert_eq!(convert(json!(u64::MAX), "bit(64)").unwrap(), Value::UInt(u64::MAX));
+
+        assert_eq!(convert(json!(true), "tinyint(1)").unwrap(), Value::Int(1));
+        assert_eq!(convert(json!("FALSE"), "boolean").unwrap(), Value::Int(0));
+        assert_eq!(convert(json!(" 42 "), "int").unwrap(), Value::Int(42));
+        assert_eq!(convert(json!("18446744073709551615"), "bigint unsigned").unwrap(), Value::UInt(u64::MAX));
+        assert!(convert(json!("4.5"), "int").is_err());
+    }
+
+    #[test]
+    fn decodes_bits_by_column_type() {
+        // 0x31 is also the text "1": the BIT column type decides it is the number 49
+        let bit8 = Column::new(ColumnType::MYSQL_TYPE_BIT).with_column_length(8);
+        let shown = to_text_value(Value::Bytes(vec![0x31]), &bit8);
+        assert_eq!(shown, Value::UInt(49));
+        assert_eq!(crate::commands::common::mysql_to_json(shown), json!(49));
+        assert_eq!(convert(json!(49), "bit(8)").unwrap(), Value::UInt(49));
+        assert_eq!(convert(json!("_binary_base64:MQ=="), "bit(8)").unwrap(), Value::UInt(49));
+        // Typed text is read as a number literal
+        assert_eq!(convert(json!("1"), "bit(8)").unwrap(), Value::UInt(1));
+
+        let bit12 = Column::new(ColumnType::MYSQL_TYPE_BIT).with_column_length(12);
+        assert_eq!(to_text_value(Value::Bytes(vec![0x0a, 0xbc]), &bit12), Value::UInt(0xabc));
+        assert_eq!(bit_width("bit(12)"), 12);
+        assert_eq!(bit_width("bit"), 1);
+    }
+
+    #[test]
+    fn converts_numbers() {
+        assert_eq!(convert(json!(1.5), "double").unwrap(), Value::Double(1.5));
+        assert_eq!(convert(json!("2.25"), "float").unwrap(), Value::Double(2.25));
+        assert!(convert(json!("abc"), "double").is_err());
+
+        assert_eq!(convert(json!("12345678901234567890.123"), "decimal(30,3)").unwrap(), Value::Bytes(b"12345678901234567890.123".to_vec()));
+        assert_eq!(convert(json!(" -.5 "), "decimal(4,2)").unwrap(), Value::Bytes(b"-.5".to_vec()));
+        assert_eq!(convert(json!(7), "numeric").unwrap(), Value::Bytes(b"7".to_vec()));
+        for bad in ["", ".", "1e5", "1.2.3", "--1", "12a"] {
+            assert!(convert(json!(bad), "decimal").is_err(), "{:?} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn converts_json_documents() {
+        assert_eq!(convert(json!({"a": [1, 2]}), "json").unwrap(), Value::Bytes(br#"{"a":[1,2]}"#.to_vec()));
+        assert_eq!(convert(json!("[1, 2]"), "json").unwrap(), Value::Bytes(b"[1, 2]".to_vec()));
+        assert_eq!(placeholder(&column("c", "json")), "CAST(? AS JSON)");
+        assert_eq!(placeholder(&column("c", "varchar(10)")), "?");
+    }
+
+    #[test]
+    fn converts_dates_and_times() {
+        assert_eq!(convert(json!("2024-02-29"), "date").unwrap(), Value::Date(2024, 2, 29, 0, 0, 0, 0));
+        assert_eq!(convert(json!("2024-02-29 13:45:07.250"), "datetime(3)").unwrap(), Value::Date(2024, 2, 29, 13, 45, 7, 250_000));
+        assert_eq!(convert(json!("2024-02-29T13:45:07"), "timestamp").unwrap(), Value::Date(2024, 2, 29, 13, 45, 7, 0));
+        // Zero dates are valid to the server but not to chrono
+        assert_eq!(convert(json!("0000-00-00 00:00:00"), "datetime").unwrap(), Value::Bytes(b"0000-00-00 00:00:00".to_vec()));
+        assert!(convert(json!(20240229), "date").is_err());
+
+        assert_eq!(convert(json!("-1d 02:00:00.000"), "time").unwrap(), Value::Bytes(b"-1 02:00:00.000".to_vec()));
+        assert_eq!(convert(json!("838:59:59"), "time").unwrap(), Value::Bytes(b"838:59:59".to_vec()));
+    }
+
+    #[test]
+    fn rewrites_time_text() {
+        assert_eq!(time_text("0d 12:30:00.000"), "0 12:30:00.000");
+        assert_eq!(time_text("-2d 01:00:00"), "-2 01:00:00");
+        assert_eq!(time_text("12:30:00"), "12:30:00");
+        assert_eq!(time_text("d 12:30"), "d 12:30");
+        assert_eq!(time_text("xd 12:30"), "xd 12:30");
+    }
+
+    #[test]
+    fn formats_fractional_seconds() {
+        assert_eq!(fraction(250_000, 0), "");
+        assert_eq!(fraction(250_000, 3), ".250");
+        assert_eq!(fraction(123_456, 6), ".123456");
+        assert_eq!(fraction(123_456, 31), ".123456");
+        assert_eq!(fraction(0, 31), "");
+    }
+
+    #[test]
+    fn renders_binary_values_as_text() {
+        let date = Column::new(ColumnType::MYSQL_TYPE_DATE);
+        assert_eq!(to_text_value(Value::Date(2024, 1, 2, 0, 0, 0, 0), &date), Value::Bytes(b"2024-01-02".to_vec()));
+
+        let datetime = Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_decimals(2);
+        assert_eq!(to_text_value(Value::Date(2024, 1, 2, 3, 4, 5, 670_000), &datetime), Value::Bytes(b"2024-01-02 03:04:05.67".to_vec()));
+
+        let time = Column::new(ColumnType::MYSQL_TYPE_TIME);
+        assert_eq!(to_text_value(Value::Time(true, 1, 2, 3, 4, 0), &time), Value::Bytes(b"-26:03:04".to_vec()));
+
+        let int = Column::new(ColumnType::MYSQL_TYPE_LONGLONG);
+        assert_eq!(to_text_value(Value::Int(-7), &int), Value::Bytes(b"-7".to_vec()));
+        assert_eq!(to_text_value(Value::NULL, &int), Value::NULL);
+    }
+
+    #[test]
+    fn compares_approximate_keys_within_a_tolerance() {
+        let (sql, params) = key_comparison("`c`", &json!("100000000000000000000"), &column("c", "double")).unwrap();
+        assert_eq!(sql, "ABS(`c` - ?) <= ?");
+        assert_eq!(params, vec![Value::Double(1e20), Value::Double(1e20 * DOUBLE_TOLERANCE)]);
+
+        // MySQL's exponent notation parses to the same number
+        let (_, params) = key_comparison("`c`", &json!("-1e-7"), &column("c", "float")).unwrap();
+        assert_eq!(params, vec![Value::Double(-1e-7), Value::Double(1e-7 * FLOAT_TOLERANCE)]);
+
+        let (sql, params) = key_comparison("`c`", &json!(null), &column("c", "double")).unwrap();
+        assert_eq!(sql, "`c` IS NULL");
+        assert!(params.is_empty());
+
+        let (sql, params) = key_comparison("`c`", &json!(3), &column("c", "int")).unwrap();
+        assert_eq!(sql, "`c` <=> ?");
+        assert_eq!(params, vec![Value::Int(3)]);
+
+        assert!(key_comparison("`c`", &json!("abc"), &column("c", "float")).is_err());
+    }
+}
@@ -13,7 +13,7 @@ pub struct SearchResult {
 }
 
 #[tauri::command]
-pub async fn global_search(term: String, db: Option<String>, state: State<'_, AppState>) -> Result<Vec<SearchResult>, String> {
+pub async fn global_search(term: String, db: Option<String>, request_id: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<SearchResult>, String> {
     let term = term.trim();
     if term.is_empty() {
         return Ok(vec![]);
@@ -42,23 +42,23 @@ pub async fn global_search(term: String, db: Option<String>, state: State<'_, Ap
         }
     }
 
-    let pool_opt = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned()
-    };
-
     // If no DB connection, return commands only
-    let pool = match pool_opt {
-        Some(p) => p,
-        None => return Ok(results),
+    let session_id = match state.resolve_session_id(session_id.as_deref()) {
+        Ok(id) => id,
+        Err(_) => return Ok(results),
+    };
+    let pool = match state.get_pool(Some(&session_id)) {
+        Ok(p) => p,
+        Err(_) => return Ok(results),
     };
 
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    let tracker = state.track_query(request_id, &session_id, conn.id())?;
 
     // 2. Metadata Search (Databases & Tables)
     
     // Search Databases
-    let dbs: Vec<String> = conn.query("SHOW DATABASES").await.map_err(|e| e.to_string())?;
+    let dbs: Vec<String> = conn.query("SHOW DATABASES").await.map_err(|e| tracker.error(e))?;
     for d in dbs {
         if d.to_lowercase().contains(&term.to_lowercase()) {
             results.push(SearchResult {
@@ -84,6 +84,7 @@ pub async fn global_search(term: String, db: Option<String>, state: State<'_, Ap
     ", term);
 
     let found_tables: Vec<(String, String)> = conn.query(&tables_sql).await.unwrap_or_default();
+    tracker.check()?;
     
     for (table, schema) in found_tables {
         results.push(SearchResult {
@@ -111,6 +112,9 @@ pub async fn global_search(term: String, db: Option<String>, state: State<'_, Ap
              // Parallelizing requires cloning the pool
              
              for table in tables_to_scan {
+                 // Lookup errors are swallowed below, so a cancel has to be checked explicitly
+                 tracker.check()?;
+
                  // Find text columns
                  let cols: Vec<String> = conn.exec(
                      "SELECT COLUMN_NAME FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND (DATA_TYPE LIKE '%char%' OR DATA_TYPE LIKE '%text%')",
@@ -143,5 +147,6 @@ pub async fn global_search(term: String, db: Option<String>, state: State<'_, Ap
         }
     }
 
+    tracker.check()?;
     Ok(results)
 }
@@ -1,15 +1,23 @@
 use tauri::{AppHandle, Manager, State};
-use crate::state::{AppState, ServerConfig};
+use crate::state::{AppState, Session, SessionInfo};
+use crate::secret_store::HasSecrets;
+use crate::server_profiles::{self, ProfileRepository, ServerProfile};
+use crate::ssh_tunnel::{SshTunnel, SshTunnelConfig};
 use mysql_async::prelude::*;
+use mysql_async::{ClientIdentity, SslOpts};
 use serde::Serialize;
+use std::path::PathBuf;
 
-const SERVERS_FILE: &str = "servers.json";
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
 
 #[derive(Serialize)]
 pub struct ServerInfo {
     pub version: String,
+    pub uptime: String,
     pub user: String,
     pub ssl: bool,
+    pub ssl_cipher: Option<String>,
+    pub ssl_version: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -49,162 +57,156 @@ pub struct Collation {
 }
 
 
-#[tauri::command]
-pub async fn get_saved_servers(app_handle: AppHandle) -> Result<Vec<ServerConfig>, String> {
-    let path = app_handle.path().app_config_dir().unwrap().join(SERVERS_FILE);
-    if !path.exists() {
-        return Ok(Vec::new());
-    }
-    let content = tokio::fs::read_to_string(path).await.map_err(|e| e.to_string())?;
-    serde_json::from_str(&content).map_err(|e| e.to_string())
+fn profiles(app_handle: &AppHandle) -> Result<ProfileRepository, String> {
+    let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(ProfileRepository::new(&config_dir))
 }
 
-#[tauri::command]
-pub async fn save_server(config: ServerConfig, app_handle: AppHandle) -> Result<(), String> {
-    let config_dir = app_handle.path().app_config_dir().unwrap();
-    if !config_dir.exists() {
-        tokio::fs::create_dir_all(&config_dir).await.map_err(|e| e.to_string())?;
-    }
-    let path = config_dir.join(SERVERS_FILE);
-    
-    let mut servers = get_saved_servers(app_handle.clone()).await.unwrap_or_default();
-    if let Some(pos) = servers.iter().position(|s| s.id == config.id) {
-        servers[pos] = config;
-    } else {
-        servers.push(config);
+/// Fills in secrets from the unlocked vault.
+fn reveal_secrets(servers: &mut [ServerProfile], state: &AppState) -> Result<(), String> {
+    let guard = state.secret_store.lock().unwrap();
+    let store = guard.as_ref().ok_or("Credential vault is locked")?;
+    for server in servers.iter_mut() {
+        if let Some(secrets) = store.get(&server.id)? {
+            server.set_secrets(secrets);
+        }
     }
-    
-    let content = serde_json::to_string_pretty(&servers).map_err(|e| e.to_string())?;
-    tokio::fs::write(path, content).await.map_err(|e| e.to_string())
+    Ok(())
 }
 
-#[tauri::command]
-pub async fn delete_server(id: String, app_handle: AppHandle) -> Result<(), String> {
-    let path = app_handle.path().app_config_dir().unwrap().join(SERVERS_FILE);
-    if !path.exists() {
+/// Moves the secrets of `server` into the vault, leaving it safe to write to disk.
+fn store_secrets(server: &mut ServerProfile, state: &AppState) -> Result<(), String> {
+    let secrets = server.take_secrets();
+    if secrets.is_empty() {
         return Ok(());
     }
-    let mut servers = get_saved_servers(app_handle.clone()).await.unwrap_or_default();
-    servers.retain(|s| s.id != id);
-    let content = serde_json::to_string_pretty(&servers).map_err(|e| e.to_string())?;
-    tokio::fs::write(path, content).await.map_err(|e| e.to_string())
+    let mut guard = state.secret_store.lock().unwrap();
+    let store = guard.as_mut().ok_or("Credential vault is locked, unlock it to save passwords")?;
+    let mut merged = store.get(&server.id)?.unwrap_or_default();
+    merged.merge(secrets);
+    store.put(&server.id, &merged)
 }
 
-#[tauri::command]
-pub async fn get_server_info(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let pool = {
-        let guard = state.pool.lock().unwrap();
-        guard.as_ref().cloned().ok_or("Not connected")?
-    };
-    
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
-    
-    // Get version
-    let version: Option<String> = conn.query_first("SELECT VERSION()").await.map_err(|e| e.to_string())?;
-    
-    // Get uptime
-    let uptime: Option<String> = conn.query_first("SELECT VARIABLE_VALUE FROM information_schema.GLOBAL_STATUS WHERE VARIABLE_NAME = 'UPTIME'").await.map_err(|e| e.to_string())?;
-    
-    // Get User
-    let user: Option<String> = conn.query_first("SELECT CURRENT_USER()").await.map_err(|e| e.to_string())?;
-    
-    Ok(vec![
-        version.unwrap_or_default(),
-        uptime.unwrap_or_default(),
-        user.unwrap_or_default()
-    ])
+fn forget_secrets(id: &str, state: &AppState) -> Result<(), String> {
+    // While locked the sealed entry just stays orphaned, it is useless without the key
+    match state.secret_store.lock().unwrap().as_mut() {
+        Some(store) => store.remove(id),
+        None => Ok(()),
+    }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Clone)]
-pub struct SavedServer {
-    pub id: String,
-    pub name: String,
-    pub host: String,
-    pub port: u16,
-    pub user: String,
-    pub pass: Option<String>,
-    pub ssl: Option<bool>,
-    pub ssh_enabled: Option<bool>,
-    pub ssh_host: Option<String>,
-    pub ssh_port: Option<u16>,
-    pub ssh_user: Option<String>,
-    pub ssh_pass: Option<String>,
-    pub auto_connect: Option<bool>,
+fn redacted(mut servers: Vec<ServerProfile>) -> Vec<ServerProfile> {
+    for server in servers.iter_mut() {
+        server.take_secrets();
+    }
+    servers
 }
 
 #[tauri::command]
-pub fn get_saved_servers_local(app_handle: tauri::AppHandle) -> Result<Vec<SavedServer>, String> {
-    use std::fs;
-    let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
-    let servers_path = config_dir.join("servers.json");
-    
-    if !servers_path.exists() {
-        return Ok(vec![]);
+pub fn get_saved_servers(reveal: Option<bool>, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<ServerProfile>, String> {
+    let mut servers = profiles(&app_handle)?.list()?;
+    if reveal.unwrap_or(false) {
+        reveal_secrets(&mut servers, &state)?;
     }
-    
-    let content = fs::read_to_string(servers_path).map_err(|e| e.to_string())?;
-    let servers: Vec<SavedServer> = serde_json::from_str(&content).map_err(|e| format!("Failed to parse servers.json: {}", e))?;
-    
     Ok(servers)
 }
 
 #[tauri::command]
-pub fn save_server_local(app_handle: tauri::AppHandle, server: SavedServer) -> Result<Vec<SavedServer>, String> {
-    use std::fs;
-    let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
-    if !config_dir.exists() {
-        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+pub fn save_server(server: ServerProfile, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<ServerProfile>, String> {
+    let mut server = server;
+    // The form sends empty fields for secrets it did not change
+    for secret in [&mut server.pass, &mut server.ssh_pass, &mut server.ssh_key_passphrase] {
+        if secret.as_deref() == Some("") {
+            *secret = None;
+        }
     }
-    
-    let servers_path = config_dir.join("servers.json");
-    
-    let mut servers: Vec<SavedServer> = if servers_path.exists() {
-        let content = fs::read_to_string(&servers_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        vec![]
-    };
-    
-    // Update or Add
-    if let Some(idx) = servers.iter().position(|s| s.id == server.id) {
-        servers[idx] = server;
-    } else {
-        servers.push(server);
+    store_secrets(&mut server, &state)?;
+
+    // Plaintext secrets not yet moved into the vault stay in the file until it is unlocked
+    let repo = profiles(&app_handle)?;
+    if let Some(mut stored) = repo.load()?.into_iter().find(|p| p.id == server.id) {
+        server.set_secrets(stored.take_secrets());
     }
-    
-    let json = serde_json::to_string_pretty(&servers).map_err(|e| e.to_string())?;
-    fs::write(servers_path, json).map_err(|e| e.to_string())?;
-    
-    Ok(servers)
+    let servers = repo.upsert(server)?;
+    Ok(redacted(servers))
 }
 
 #[tauri::command]
-pub fn delete_server_local(app_handle: tauri::AppHandle, id: String) -> Result<Vec<SavedServer>, String> {
-    use std::fs;
-    let config_dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
-    let servers_path = config_dir.join("servers.json");
-    
-    if !servers_path.exists() {
-        return Ok(vec![]);
+pub fn delete_server(id: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<ServerProfile>, String> {
+    forget_secrets(&id, &state)?;
+    let servers = profiles(&app_handle)?.delete(&id)?;
+    Ok(redacted(servers))
+}
+
+/// Writes the saved servers to `path`. Secrets are never exported.
+#[tauri::command]
+pub fn export_server_profiles(path: String, ids: Option<Vec<String>>, app_handle: AppHandle) -> Result<usize, String> {
+    let mut servers = profiles(&app_handle)?.list()?;
+    if let Some(ids) = ids {
+        servers.retain(|s| ids.contains(&s.id));
+    }
+    let content = server_profiles::serialize(&servers)?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(servers.len())
+}
+
+/// Merges profiles from an exported file: same id replaces, new ids are appended.
+#[tauri::command]
+pub fn import_server_profiles(path: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<ServerProfile>, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let imported = server_profiles::parse(&content)?;
+
+    let repo = profiles(&app_handle)?;
+    let mut servers = repo.load()?;
+    for mut server in imported {
+        store_secrets(&mut server, &state)?;
+        match servers.iter().position(|s| s.id == server.id) {
+            Some(idx) => servers[idx] = server,
+            None => servers.push(server),
+        }
     }
+    repo.save(&servers)?;
+    Ok(redacted(servers))
+}
+
+#[tauri::command]
+pub async fn get_server_info(session_id: Option<String>, state: State<'_, AppState>) -> Result<ServerInfo, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     
-    let content = fs::read_to_string(&servers_path).map_err(|e| e.to_string())?;
-    let mut servers: Vec<SavedServer> = serde_json::from_str(&content).unwrap_or_default();
+    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
-    servers.retain(|s| s.id != id);
+    // Get version
+    let version: Option<String> = conn.query_first("SELECT VERSION()").await.map_err(|e| e.to_string())?;
     
-    let json = serde_json::to_string_pretty(&servers).map_err(|e| e.to_string())?;
-    fs::write(servers_path, json).map_err(|e| e.to_string())?;
+    // Get uptime
+    let uptime: Option<String> = conn.query_first("SELECT VARIABLE_VALUE FROM information_schema.GLOBAL_STATUS WHERE VARIABLE_NAME = 'UPTIME'").await.map_err(|e| e.to_string())?;
     
-    Ok(servers)
+    // Get User
+    let user: Option<String> = conn.query_first("SELECT CURRENT_USER()").await.map_err(|e| e.to_string())?;
+
+    // Negotiated TLS parameters of this session (empty values when unencrypted)
+    let tls: Vec<(String, String)> = conn.query("SHOW SESSION STATUS WHERE Variable_name IN ('Ssl_cipher', 'Ssl_version')")
+        .await.map_err(|e| e.to_string())?;
+    let tls_value = |name: &str| tls.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.clone())
+        .filter(|v| !v.is_empty());
+    let ssl_cipher = tls_value("Ssl_cipher");
+    let ssl_version = tls_value("Ssl_version");
+    
+    Ok(ServerInfo {
+        version: version.unwrap_or_default(),
+        uptime: uptime.unwrap_or_default(),
+        user: user.unwrap_or_default(),
+        ssl: ssl_cipher.is_some(),
+        ssl_cipher,
+        ssl_version,
+    })
 }
 
 #[tauri::command]
-pub async fn get_process_list(state: State<'_, AppState>) -> Result<Vec<ProcessItem>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_process_list(session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<ProcessItem>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let rows: Vec<mysql_async::Row> = conn.query("SHOW FULL PROCESSLIST").await.map_err(|e| e.to_string())?;
@@ -226,11 +228,8 @@ pub async fn get_process_list(state: State<'_, AppState>) -> Result<Vec<ProcessI
 }
 
 #[tauri::command]
-pub async fn get_status_variables(filter: Option<String>, state: State<'_, AppState>) -> Result<Vec<StatusVar>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_status_variables(filter: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<StatusVar>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let query = if let Some(f) = filter {
@@ -252,11 +251,8 @@ pub async fn get_status_variables(filter: Option<String>, state: State<'_, AppSt
 }
 
 #[tauri::command]
-pub async fn get_server_variables(filter: Option<String>, state: State<'_, AppState>) -> Result<Vec<StatusVar>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_server_variables(filter: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<StatusVar>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let query = if let Some(f) = filter {
@@ -279,21 +275,202 @@ pub async fn get_server_variables(filter: Option<String>, state: State<'_, AppSt
 
 #[tauri::command]
 pub async fn connect_db(config: crate::state::DbConfig, state: State<'_, AppState>) -> Result<String, String> {
-    use mysql_async::{Opts, Pool};
-    let opts = Opts::from_url(&format!(
-        "mysql://{}:{}@{}:{}/",
-        config.user, config.pass, config.host, config.port
-    )).map_err(|e| e.to_string())?;
+    use mysql_async::{Compression, OptsBuilder, Pool};
+
+    // Behind a bastion: dial the database through a local SSH port forward
+    let tunnel = if config.ssh_enabled.unwrap_or(false) {
+        Some(open_ssh_tunnel(&config).await?)
+    } else {
+        None
+    };
+    let (host, port) = match &tunnel {
+        Some(t) => ("127.0.0.1".to_string(), t.local_port),
+        None => (config.host.clone(), config.port),
+    };
+
+    let mut ssl_opts = build_ssl_opts(&config)?;
+    if tunnel.is_some() {
+        // The certificate names the database host, not the tunnel's 127.0.0.1
+        ssl_opts = ssl_opts.map(|o| o.with_danger_tls_hostname_override(Some(config.host.clone())));
+    }
+
+    // Set each field directly: credentials may contain URL-reserved characters
+    let mut opts = OptsBuilder::default()
+        .ip_or_hostname(host)
+        .tcp_port(port)
+        .user(Some(config.user.clone()))
+        .pass(Some(config.pass.clone()).filter(|p| !p.is_empty()))
+        .db_name(config.database.clone().filter(|d| !d.is_empty()))
+        .ssl_opts(ssl_opts);
+
+    // A unix socket only makes sense for a direct local connection
+    let socket = config.socket.clone().filter(|s| !s.is_empty() && tunnel.is_none());
+    // Otherwise stay on TCP: by default the driver switches to the server's own @@socket
+    // path, which through a tunnel would be a socket on this machine, maybe another server
+    opts = opts.socket(socket).prefer_socket(false);
+
+    if let Some(level) = config.compression_level {
+        opts = opts.compression(Compression::new(level.min(9)));
+    }
 
     let pool = Pool::new(opts);
     
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    let connect_timeout = std::time::Duration::from_secs(config.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS));
+    let mut conn = tokio::time::timeout(connect_timeout, pool.get_conn())
+        .await
+        .map_err(|_| format!("Connection timed out after {}s", connect_timeout.as_secs()))?
+        .map_err(|e| e.to_string())?;
     let _ : Vec<String> = conn.query("SELECT 1").await.map_err(|e| e.to_string())?;
+    drop(conn);
+
+    // Saved servers are keyed by their id, ad-hoc connections get a fresh one
+    let session_id = config.id.clone()
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let session = Session {
+        id: session_id.clone(),
+        name: config.name.clone().unwrap_or_else(|| format!("{}@{}", config.user, config.host)),
+        host: config.host.clone(),
+        port: config.port,
+        user: config.user.clone(),
+        connected_at: chrono::Local::now().to_rfc3339(),
+        pool,
+        tunnel,
+        transaction: None,
+    };
+
+    // Reconnecting the same server replaces its previous session
+    let previous = {
+        let mut sessions = state.sessions.lock().unwrap();
+        sessions.insert(session_id.clone(), session)
+    };
+    *state.active_session.lock().unwrap() = Some(session_id.clone());
+
+    if let Some(old) = previous {
+        let _ = close_session(old).await;
+    }
+
+    Ok(session_id)
+}
+
+/// Maps the TLS settings onto `SslOpts`. `None` means a plain connection.
+fn build_ssl_opts(config: &crate::state::DbConfig) -> Result<Option<SslOpts>, String> {
+    // Legacy profiles only carry the `ssl` flag, which meant "encrypt"
+    let mode = match config.ssl_mode.as_deref().filter(|m| !m.is_empty()) {
+        Some(m) => m.to_lowercase(),
+        None if config.ssl.unwrap_or(false) => "required".to_string(),
+        None => "disabled".to_string(),
+    };
+
+    let (accept_invalid_certs, skip_domain_validation) = match mode.as_str() {
+        "disabled" => return Ok(None),
+        "required" => (true, true),
+        "verify_ca" => (false, true),
+        "verify_identity" => (false, false),
+        other => return Err(format!("Unknown SSL mode: {}", other)),
+    };
+    let skip_verify = config.ssl_skip_verify.unwrap_or(false);
+
+    let mut ssl_opts = SslOpts::default()
+        .with_danger_accept_invalid_certs(accept_invalid_certs || skip_verify)
+        .with_danger_skip_domain_validation(skip_domain_validation || skip_verify);
+
+    if let Some(ca) = config.ssl_ca_path.as_ref().filter(|p| !p.is_empty()) {
+        ssl_opts = ssl_opts.with_root_certs(vec![PathBuf::from(ca).into()]);
+    }
+
+    let cert = config.ssl_cert_path.as_ref().filter(|p| !p.is_empty());
+    let key = config.ssl_key_path.as_ref().filter(|p| !p.is_empty());
+    match (cert, key) {
+        (Some(cert), Some(key)) => {
+            let identity = ClientIdentity::new(PathBuf::from(cert).into(), PathBuf::from(key).into());
+            ssl_opts = ssl_opts.with_client_identity(Some(identity));
+        }
+        (None, None) => {}
+        _ => return Err("Both a client certificate and a client key are required".to_string()),
+    }
+
+    Ok(Some(ssl_opts))
+}
+
+async fn open_ssh_tunnel(config: &crate::state::DbConfig) -> Result<SshTunnel, String> {
+    let ssh_host = config.ssh_host.clone().filter(|h| !h.is_empty()).ok_or("SSH host is required")?;
+    let ssh_config = SshTunnelConfig {
+        host: ssh_host,
+        port: config.ssh_port.unwrap_or(22),
+        user: config.ssh_user.clone().unwrap_or_default(),
+        pass: config.ssh_pass.clone().filter(|p| !p.is_empty()),
+        key_path: config.ssh_key_path.clone(),
+        key_passphrase: config.ssh_key_passphrase.clone(),
+        known_hosts_path: config.ssh_known_hosts_path.clone(),
+        strict_host_key: config.ssh_strict_host_key.unwrap_or(false),
+    };
+    let remote_host = config.host.clone();
+    let remote_port = config.port;
+
+    // libssh2 is blocking, keep it off the async runtime
+    tokio::task::spawn_blocking(move || SshTunnel::open(&ssh_config, &remote_host, remote_port))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Disconnects the pool first so pooled connections close cleanly, then drops the tunnel.
+async fn close_session(mut session: Session) -> Result<(), String> {
+    // Pool::disconnect waits for every connection to come back, including a pinned one.
+    // Releasing it returns the connection, and the pool's reset discards the open transaction.
+    session.transaction = None;
+    let result = session.pool.disconnect().await.map_err(|e| e.to_string());
+    if let Some(tunnel) = session.tunnel {
+        let _ = tokio::task::spawn_blocking(move || tunnel.close()).await;
+    }
+    result
+}
+
+#[tauri::command]
+pub fn list_sessions(state: State<'_, AppState>) -> Result<Vec<SessionInfo>, String> {
+    let active = state.active_session.lock().unwrap().clone();
+    let sessions = state.sessions.lock().unwrap();
+
+    let mut list: Vec<SessionInfo> = sessions.values().map(|s| SessionInfo {
+        id: s.id.clone(),
+        name: s.name.clone(),
+        host: s.host.clone(),
+        port: s.port,
+        user: s.user.clone(),
+        connected_at: s.connected_at.clone(),
+        active: active.as_deref() == Some(s.id.as_str()),
+    }).collect();
+    list.sort_by(|a, b| a.connected_at.cmp(&b.connected_at));
 
-    let mut guard = state.pool.lock().unwrap();
-    *guard = Some(pool);
+    Ok(list)
+}
 
-    Ok("Connected successfully".into())
+#[tauri::command]
+pub fn switch_session(session_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    if !state.sessions.lock().unwrap().contains_key(&session_id) {
+        return Err(format!("Session '{}' is not connected", session_id));
+    }
+    *state.active_session.lock().unwrap() = Some(session_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn disconnect_session(session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let id = state.resolve_session_id(session_id.as_deref())?;
+
+    let removed = state.sessions.lock().unwrap().remove(&id);
+    let session = removed.ok_or_else(|| format!("Session '{}' is not connected", id))?;
+
+    {
+        // Fall back to any remaining session when the active one goes away
+        let mut active = state.active_session.lock().unwrap();
+        if active.as_deref() == Some(id.as_str()) {
+            *active = state.sessions.lock().unwrap().keys().next().cloned();
+        }
+    }
+
+    close_session(session).await
 }
 
 #[derive(Serialize)]
@@ -308,11 +485,8 @@ pub struct MonitorData {
 }
 
 #[tauri::command]
-pub async fn get_monitor_data(state: State<'_, AppState>) -> Result<MonitorData, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_monitor_data(session_id: Option<String>, state: State<'_, AppState>) -> Result<MonitorData, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     // Fetch necessary variables in one query for consistency
@@ -357,11 +531,8 @@ pub async fn get_monitor_data(state: State<'_, AppState>) -> Result<MonitorData,
 }
 
 #[tauri::command]
-pub async fn get_charsets(state: State<'_, AppState>) -> Result<Vec<Charset>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_charsets(session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<Charset>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let rows: Vec<mysql_async::Row> = conn.query("SHOW CHARSET").await.map_err(|e| e.to_string())?;
@@ -379,11 +550,8 @@ pub async fn get_charsets(state: State<'_, AppState>) -> Result<Vec<Charset>, St
 }
 
 #[tauri::command]
-pub async fn get_collations_full(state: State<'_, AppState>) -> Result<Vec<Collation>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_collations_full(session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<Collation>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let rows: Vec<mysql_async::Row> = conn.query("SHOW COLLATION").await.map_err(|e| e.to_string())?;
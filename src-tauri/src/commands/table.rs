@@ -1,6 +1,8 @@
 use tauri::State;
 use crate::state::AppState;
 use crate::commands::common::{mysql_to_json, render_table_html, render_pagination_html};
+use crate::commands::row_values::{key_comparison, to_column_value, to_text_value};
+use crate::commands::filter::{Combinator, FilterNode};
 use mysql_async::prelude::*;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
@@ -10,7 +12,33 @@ pub struct BrowseResultRaw {
     pub columns: Vec<String>,
     pub rows: Vec<Vec<serde_json::Value>>,
     pub total_rows: u64,
+    /// Set only when the primary key is a single column; see `row_identity` otherwise
     pub primary_key: Option<String>,
+    pub row_identity: RowIdentity,
+}
+
+/// Columns the grid uses to address a row when editing or deleting it: the primary key,
+/// else the narrowest unique index over NOT NULL columns, else every column.
+#[derive(Serialize, Debug)]
+pub struct RowIdentity {
+    pub kind: String, // "primary" | "unique" | "full_row"
+    pub columns: Vec<String>,
+}
+
+/// Outcome of a grid edit. An UPDATE can match a row without changing it, so `matched`
+/// may exceed `affected`.
+#[derive(Serialize, Debug)]
+pub struct EditResult {
+    pub matched: u64,
+    pub affected: u64,
+    /// Set when an update was given the original values and the row no longer has them
+    pub conflict: Option<EditConflict>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EditConflict {
+    /// The row as it is now, or `None` if it was deleted
+    pub current: Option<HashMap<String, serde_json::Value>>,
 }
 
 #[derive(Serialize)]
@@ -54,11 +82,8 @@ pub struct IndexInfo {
 use crate::commands::query::QueryResultHtml;
 
 #[tauri::command]
-pub async fn get_tables(db: String, state: State<'_, AppState>) -> Result<Vec<TableInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_tables(db: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<TableInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     conn.query_drop(format!("USE `{}`", db)).await.map_err(|e| e.to_string())?;
@@ -248,12 +273,12 @@ fn render_detailed_structure_html(columns: &[ColumnInfo], indexes: &[crate::comm
 }
 
 #[tauri::command]
-pub async fn get_tables_html(db: String, table: Option<String>, state: State<'_, AppState>) -> Result<TablesResultHtml, String> {
+pub async fn get_tables_html(db: String, table: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<TablesResultHtml, String> {
     if let Some(tbl) = table {
         // Detailed Structure View (Columns + Indexes)
-        let columns = get_columns(db.clone(), tbl.clone(), state.clone()).await?;
+        let columns = get_columns(db.clone(), tbl.clone(), session_id.clone(), state.clone()).await?;
         // Need to call proper module for indexes
-        let indexes = crate::commands::indexes::get_indexes(db.clone(), tbl.clone(), state.clone()).await?;
+        let indexes = crate::commands::indexes::get_indexes(db.clone(), tbl.clone(), session_id.clone(), state.clone()).await?;
         
         // Render
         let body_html = render_detailed_structure_html(&columns, &indexes);
@@ -264,7 +289,7 @@ pub async fn get_tables_html(db: String, table: Option<String>, state: State<'_,
     }
 
     // Default Table List View
-    let tables = get_tables(db, state).await?;
+    let tables = get_tables(db, session_id, state).await?;
     let body_html = render_structure_html(&tables);
     
     Ok(TablesResultHtml {
@@ -274,14 +299,11 @@ pub async fn get_tables_html(db: String, table: Option<String>, state: State<'_,
 }
 
 #[tauri::command]
-pub async fn browse_table_html(db: String, table: String, page: u32, limit: u32, state: State<'_, AppState>) -> Result<QueryResultHtml, String> {
+pub async fn browse_table_html(db: String, table: String, page: u32, limit: u32, session_id: Option<String>, state: State<'_, AppState>) -> Result<QueryResultHtml, String> {
     let start = std::time::Instant::now();
     let offset = (page - 1) * limit;
     
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     // 1. Get Count
@@ -353,58 +375,62 @@ pub async fn browse_table(
     sort_column: Option<String>,
     sort_direction: Option<String>,
     filters: Option<Vec<Filter>>,
+    filter: Option<FilterNode>,
+    request_id: Option<String>,
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<BrowseResultRaw, String> {
     let offset = (page - 1) * limit;
     
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    let session_id = state.resolve_session_id(session_id.as_deref())?;
+    let mut conn = state.get_conn(Some(&session_id)).await?;
+    let tracker = state.track_query(request_id, &session_id, conn.id())?;
     
-    // Build WHERE clause
-    let mut where_clause = String::new();
-    if let Some(fs) = filters {
-        if !fs.is_empty() {
-             let conditions: Vec<String> = fs.iter().map(|f| {
-                 let safe_op = match f.op.as_str() {
-                     "=" | "!=" | ">" | "<" | ">=" | "<=" | "LIKE" | "NOT LIKE" => f.op.as_str(),
-                     _ => "=", // Fallback default
-                 };
-                 // Simple escaping for value
-                 let safe_val = f.val.replace("'", "''"); // Basic MySQL escape
-                 format!("`{}` {} '{}'", f.col, safe_op, safe_val)
-             }).collect();
-             where_clause = format!("WHERE {}", conditions.join(" AND "));
-        }
+    // Column names in filters and sorting are checked against these
+    let table_columns = load_columns(&mut conn, &db, &table).await.map_err(|e| tracker.error(e))?;
+
+    // Build WHERE clause from the simple filters and the filter tree
+    let mut nodes = Vec::new();
+    if let Some(fs) = filters.filter(|fs| !fs.is_empty()) {
+        nodes.push(FilterNode::from_filters(&fs));
     }
+    nodes.extend(filter);
+    let (where_clause, params) = if nodes.is_empty() {
+        (String::new(), Vec::new())
+    } else {
+        let root = FilterNode::Group { combinator: Combinator::And, negated: false, children: nodes };
+        let (condition, params) = root.compile(&table_columns)?;
+        (format!("WHERE {}", condition), params)
+    };
 
     // 1. Get Count
-    let count_sql = format!("SELECT count(*) FROM `{}`.`{}` {}", db, table, where_clause);
-    let count: Option<u64> = conn.query_first(count_sql).await.map_err(|e| e.to_string())?;
+    let count_sql = format!("SELECT count(*) FROM {}.{} {}", quote_ident(&db), quote_ident(&table), where_clause);
+    let count: Option<u64> = conn.exec_first(count_sql, params.clone()).await.map_err(|e| tracker.error(e))?;
     let total_rows = count.unwrap_or(0);
 
-    // 2. Get Primary Key (needed for fallback sort or for frontend knowledge)
-    let pk_query = format!("SHOW KEYS FROM `{}`.`{}` WHERE Key_name = 'PRIMARY'", db, table);
-    let pk_row: Option<mysql_async::Row> = conn.query_first(pk_query).await.map_err(|e| e.to_string())?;
-    
-    let pk_col = pk_row.map(|row| row.get::<String, _>("Column_name").unwrap_or_default());
-    
+    // 2. Work out the row identity (needed for fallback sort and for editing)
+    let keys: Vec<mysql_async::Row> = conn
+        .query(format!("SHOW KEYS FROM {}.{}", quote_ident(&db), quote_ident(&table)))
+        .await
+        .map_err(|e| tracker.error(e))?;
+    let key_identity = identity_from_keys(&keys);
+
     // 3. Determine Sorting
     let order_by = if let Some(col) = sort_column {
+        let column = find_column(&table_columns, &col)?;
         let dir = sort_direction.unwrap_or_else(|| "ASC".to_string()).to_uppercase();
         let safe_dir = if dir == "DESC" { "DESC" } else { "ASC" };
-        format!("ORDER BY `{}` {}", col, safe_dir)
-    } else if let Some(ref col_name) = pk_col {
-        format!("ORDER BY `{}` ASC", col_name)
+        format!("ORDER BY {} {}", quote_ident(&column.field), safe_dir)
+    } else if let Some(ref identity) = key_identity {
+        let cols: Vec<String> = identity.columns.iter().map(|c| format!("{} ASC", quote_ident(c))).collect();
+        format!("ORDER BY {}", cols.join(", "))
     } else {
         "".to_string()
     };
 
     // 4. Get Data
-    let sql = format!("SELECT * FROM `{}`.`{}` {} {} LIMIT {} OFFSET {}", db, table, where_clause, order_by, limit, offset);
-    let mut result = conn.query_iter(sql).await.map_err(|e| e.to_string())?;
+    let sql = format!("SELECT * FROM {}.{} {} {} LIMIT {} OFFSET {}", quote_ident(&db), quote_ident(&table), where_clause, order_by, limit, offset);
+    let mut result = conn.exec_iter(sql, params).await.map_err(|e| tracker.error(e))?;
     
     let mut columns = Vec::new();
     if let Some(col_slice) = result.columns() {
@@ -413,115 +439,300 @@ pub async fn browse_table(
         }
     }
 
-    let rows_data: Vec<mysql_async::Row> = result.collect().await.map_err(|e| e.to_string())?;
+    let rows_data: Vec<mysql_async::Row> = result.collect().await.map_err(|e| tracker.error(e))?;
+    tracker.check()?;
     let mut rows = Vec::new();
 
     for row in rows_data {
         let mut row_values = Vec::new();
         for i in 0..columns.len() {
             let val: mysql_async::Value = row.get(i).unwrap_or(mysql_async::Value::NULL);
-            row_values.push(mysql_to_json(val));
+            // Prepared results are binary; keep cells identical to what the text protocol sends
+            row_values.push(mysql_to_json(to_text_value(val, &row.columns_ref()[i])));
         }
         rows.push(row_values);
     }
     
+    let row_identity = key_identity.unwrap_or_else(|| RowIdentity {
+        kind: "full_row".to_string(),
+        columns: columns.clone(),
+    });
+    let primary_key = match (row_identity.kind.as_str(), row_identity.columns.as_slice()) {
+        ("primary", [col]) => Some(col.clone()),
+        _ => None,
+    };
+
     Ok(BrowseResultRaw {
         columns,
         rows,
         total_rows,
-        primary_key: pk_col,
+        primary_key,
+        row_identity,
     })
 }
 
+/// One column of an index: (seq_in_index, column, nullable). The column is `None` for
+/// functional key parts.
+type KeyPart = (u32, Option<String>, bool);
+
+/// Picks the row identity from `SHOW KEYS` output: all primary key columns, else the unique
+/// index with the fewest columns, provided none of them is nullable or an expression.
+fn identity_from_keys(keys: &[mysql_async::Row]) -> Option<RowIdentity> {
+    // Unique indexes in SHOW KEYS order
+    let mut indexes: Vec<(String, Vec<KeyPart>)> = Vec::new();
+    for row in keys {
+        if row.get::<Option<u64>, _>("Non_unique").flatten().unwrap_or(1) != 0 {
+            continue;
+        }
+        let name = row.get::<Option<String>, _>("Key_name").flatten().unwrap_or_default();
+        let part = (
+            row.get::<Option<u32>, _>("Seq_in_index").flatten().unwrap_or(0),
+            row.get::<Option<String>, _>("Column_name").flatten(),
+            row.get::<Option<String>, _>("Null").flatten().unwrap_or_default() == "YES",
+        );
+        match indexes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, parts)) => parts.push(part),
+            None => indexes.push((name, vec![part])),
+        }
+    }
+
+    if let Some((_, parts)) = indexes.iter().find(|(name, _)| name == "PRIMARY") {
+        return Some(RowIdentity { kind: "primary".to_string(), columns: key_columns(parts) });
+    }
+    indexes
+        .iter()
+        .filter(|(_, parts)| parts.iter().all(|(_, col, nullable)| col.is_some() && !nullable))
+        .min_by_key(|(_, parts)| parts.len())
+        .map(|(_, parts)| RowIdentity { kind: "unique".to_string(), columns: key_columns(parts) })
+}
+
+fn key_columns(parts: &[KeyPart]) -> Vec<String> {
+    let mut parts = parts.to_vec();
+    parts.sort_by_key(|(seq, _, _)| *seq);
+    parts.into_iter().filter_map(|(_, col, _)| col).collect()
+}
+
+/// Builds the WHERE condition addressing one row, with its parameters. `<=>` lets full-row
+/// keys match NULL columns; callers add `LIMIT 1` so a key that matches duplicate rows only
+/// touches one of them.
+fn key_condition(key: &HashMap<String, serde_json::Value>, columns: &[ColumnInfo]) -> Result<(String, Vec<mysql_async::Value>), String> {
+    if key.is_empty() {
+        return Err("Row key is empty".to_string());
+    }
+    let mut parts = Vec::new();
+    let mut params = Vec::new();
+    for (col, val) in key {
+        let column = find_column(columns, col)?;
+        let (condition, values) = key_comparison(&quote_ident(&column.field), val, column)?;
+        parts.push(condition);
+        params.extend(values);
+    }
+    Ok((parts.join(" AND "), params))
+}
+
+/// `SET` assignments for `row`, with their parameters.
+fn set_clause(row: &HashMap<String, serde_json::Value>, columns: &[ColumnInfo]) -> Result<(String, Vec<mysql_async::Value>), String> {
+    let mut parts = Vec::new();
+    let mut params = Vec::new();
+    for (col, val) in row {
+        let column = find_column(columns, col)?;
+        parts.push(format!("{} = ?", quote_ident(&column.field)));
+        params.push(to_column_value(val, column)?);
+    }
+    Ok((parts.join(", "), params))
+}
+
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Looks up a column by name, which also keeps unknown identifiers out of generated SQL.
+pub(crate) fn find_column<'a>(columns: &'a [ColumnInfo], name: &str) -> Result<&'a ColumnInfo, String> {
+    columns
+        .iter()
+        .find(|c| c.field.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("Unknown column `{}`", name))
+}
+
+/// Rows found by the last UPDATE, from the OK packet's "Rows matched: N  Changed: M" info.
+pub(crate) fn rows_matched(conn: &mysql_async::Conn) -> u64 {
+    conn.info()
+        .strip_prefix("Rows matched:")
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|n| n.parse().ok())
+        .unwrap_or_else(|| conn.affected_rows())
+}
+
 #[tauri::command]
 pub async fn update_cell(
     db: String, 
     table: String, 
     column: String, 
     value: serde_json::Value, 
-    primary_key_col: String,
-    primary_key_val: serde_json::Value,
+    key: HashMap<String, serde_json::Value>,
+    original: Option<HashMap<String, serde_json::Value>>,
+    session_id: Option<String>,
     state: State<'_, AppState>
-) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
-
-    // Determine value representation (escape if string)
-    let val_str = match value {
-        serde_json::Value::String(s) => format!("'{}'", s.replace("'", "''")),
-        serde_json::Value::Null => "NULL".to_string(),
-        _ => value.to_string(),
-    };
-
-    let pk_val_str = match primary_key_val {
-        serde_json::Value::String(s) => format!("'{}'", s.replace("'", "''")),
-        _ => primary_key_val.to_string(),
-    };
-
-    let sql = format!(
-        "UPDATE `{}`.`{}` SET `{}` = {} WHERE `{}` = {}", 
-        db, table, column, val_str, primary_key_col, pk_val_str
-    );
-
-    conn.query_drop(sql).await.map_err(|e| e.to_string())
+) -> Result<EditResult, String> {
+    let row = HashMap::from([(column, value)]);
+    update_row(db, table, row, key, original, session_id, state).await
 }
 
+/// Updates the row identified by `key`. With `original` (the values the grid loaded), the
+/// row is only changed if it still holds them; otherwise the result carries a conflict with
+/// the current values so the caller can merge or overwrite.
 #[tauri::command]
 pub async fn update_row(
     db: String, 
     table: String, 
     row: HashMap<String, serde_json::Value>, 
-    primary_key_col: String,
-    primary_key_val: serde_json::Value,
+    key: HashMap<String, serde_json::Value>,
+    original: Option<HashMap<String, serde_json::Value>>,
+    session_id: Option<String>,
     state: State<'_, AppState>
-) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
+) -> Result<EditResult, String> {
+    if row.is_empty() {
+        return Ok(EditResult { matched: 0, affected: 0, conflict: None });
+    }
+
+    let mut conn = state.get_conn(session_id.as_deref()).await?;
+    let columns = load_columns(&mut conn, &db, &table).await?;
+
+    let (sql, params) = update_statement(&db, &table, &row, &key, original.as_ref(), &columns)?;
+    conn.exec_drop(sql, params).await.map_err(|e| e.to_string())?;
+    let matched = rows_matched(&conn);
+    let affected = conn.affected_rows();
+
+    let conflict = if original.is_some() && matched == 0 {
+        let current = fetch_row(&mut conn, &db, &table, &key, &columns).await?;
+        Some(EditConflict { current })
+    } else {
+        None
     };
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    Ok(EditResult { matched, affected, conflict })
+}
 
-    let mut set_clauses = Vec::new();
-    for (col, val) in row {
-        let val_str = match val {
-            serde_json::Value::String(s) => format!("'{}'", s.replace("'", "''").replace("\\", "\\\\")),
-            serde_json::Value::Null => "NULL".to_string(),
-            _ => val.to_string(),
-        };
-        set_clauses.push(format!("`{}` = {}", col, val_str));
+/// `UPDATE` of the row identified by `key`, which must also still hold `original` if given.
+pub(crate) fn update_statement(
+    db: &str,
+    table: &str,
+    row: &HashMap<String, serde_json::Value>,
+    key: &HashMap<String, serde_json::Value>,
+    original: Option<&HashMap<String, serde_json::Value>>,
+    columns: &[ColumnInfo],
+) -> Result<(String, Vec<mysql_async::Value>), String> {
+    let mut expected = key.clone();
+    if let Some(original) = original {
+        expected.extend(original.iter().map(|(k, v)| (k.clone(), v.clone())));
     }
 
-    if set_clauses.is_empty() {
-        return Ok(());
-    }
+    let (set_sql, mut params) = set_clause(row, columns)?;
+    let (where_sql, key_params) = key_condition(&expected, columns)?;
+    params.extend(key_params);
 
-    let pk_val_str = match primary_key_val {
-        serde_json::Value::String(s) => format!("'{}'", s.replace("'", "''")),
-        _ => primary_key_val.to_string(),
-    };
+    let sql = format!("UPDATE {}.{} SET {} WHERE {} LIMIT 1", quote_ident(db), quote_ident(table), set_sql, where_sql);
+    Ok((sql, params))
+}
+
+/// `DELETE` of the row identified by `key`.
+pub(crate) fn delete_statement(
+    db: &str,
+    table: &str,
+    key: &HashMap<String, serde_json::Value>,
+    columns: &[ColumnInfo],
+) -> Result<(String, Vec<mysql_async::Value>), String> {
+    let (where_sql, params) = key_condition(key, columns)?;
+    let sql = format!("DELETE FROM {}.{} WHERE {} LIMIT 1", quote_ident(db), quote_ident(table), where_sql);
+    Ok((sql, params))
+}
+
+/// Multi-row `INSERT` of `rows`. A column missing from a row gets its DEFAULT, and the string
+/// "NULL" (any case) is taken as NULL for the benefit of plain text forms.
+pub(crate) fn insert_statement(
+    db: &str,
+    table: &str,
+    rows: &[HashMap<String, serde_json::Value>],
+    table_columns: &[ColumnInfo],
+) -> Result<(String, Vec<mysql_async::Value>), String> {
+    let columns = insert_columns(rows, table_columns)?;
+    let col_list = columns.iter().map(|c| quote_ident(&c.field)).collect::<Vec<_>>().join(", ");
+
+    let mut values_parts: Vec<String> = Vec::new();
+    let mut params: Vec<mysql_async::Value> = Vec::new();
+    for row in rows {
+        let mut row_vals: Vec<&str> = Vec::new();
+        for column in &columns {
+            let val = row.iter().find(|(k, _)| k.eq_ignore_ascii_case(&column.field)).map(|(_, v)| v);
+            match val {
+                None => row_vals.push("DEFAULT"),
+                Some(serde_json::Value::String(s)) if s.eq_ignore_ascii_case("NULL") => row_vals.push("NULL"),
+                Some(v) => {
+                    row_vals.push("?");
+                    params.push(to_column_value(v, column)?);
+                }
+            }
+        }
+        values_parts.push(format!("({})", row_vals.join(", ")));
+    }
 
     let sql = format!(
-        "UPDATE `{}`.`{}` SET {} WHERE `{}` = {}", 
-        db, table, set_clauses.join(", "), primary_key_col, pk_val_str
+        "INSERT INTO {}.{} ({}) VALUES {}",
+        quote_ident(db), quote_ident(table), col_list, values_parts.join(", ")
     );
+    Ok((sql, params))
+}
 
-    conn.query_drop(sql).await.map_err(|e| e.to_string())
+/// Every column named by any row, in first-seen order.
+fn insert_columns<'a>(rows: &[HashMap<String, serde_json::Value>], table_columns: &'a [ColumnInfo]) -> Result<Vec<&'a ColumnInfo>, String> {
+    let mut columns: Vec<&ColumnInfo> = Vec::new();
+    for row in rows {
+        for col in row.keys() {
+            let column = find_column(table_columns, col)?;
+            if !columns.iter().any(|c| c.field == column.field) {
+                columns.push(column);
+            }
+        }
+    }
+    if columns.is_empty() {
+        return Err("No columns to insert".to_string());
+    }
+    Ok(columns)
+}
+
+/// The row identified by `key` as the grid would show it, or `None` if there is none.
+async fn fetch_row(
+    conn: &mut mysql_async::Conn,
+    db: &str,
+    table: &str,
+    key: &HashMap<String, serde_json::Value>,
+    columns: &[ColumnInfo],
+) -> Result<Option<HashMap<String, serde_json::Value>>, String> {
+    let (where_sql, params) = key_condition(key, columns)?;
+    let sql = format!("SELECT * FROM {}.{} WHERE {} LIMIT 1", quote_ident(db), quote_ident(table), where_sql);
+    let row: Option<mysql_async::Row> = conn.exec_first(sql, params).await.map_err(|e| e.to_string())?;
+
+    Ok(row.map(|row| {
+        row.columns_ref()
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                let val: mysql_async::Value = row.get(i).unwrap_or(mysql_async::Value::NULL);
+                (col.name_str().into_owned(), mysql_to_json(to_text_value(val, col)))
+            })
+            .collect()
+    }))
 }
 
 #[tauri::command]
-pub async fn get_columns(db: String, table: String, state: State<'_, AppState>) -> Result<Vec<ColumnInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_columns(db: String, table: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<ColumnInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    load_columns(&mut conn, &db, &table).await
+}
 
-    conn.query_drop(format!("USE `{}`", db)).await.map_err(|e| e.to_string())?;
-    
-    let query = format!("SHOW FULL COLUMNS FROM `{}`.`{}`", db, table);
+/// Column definitions of `db`.`table`, as `SHOW FULL COLUMNS` reports them.
+pub(crate) async fn load_columns(conn: &mut mysql_async::Conn, db: &str, table: &str) -> Result<Vec<ColumnInfo>, String> {
+    let query = format!("SHOW FULL COLUMNS FROM {}.{}", quote_ident(db), quote_ident(table));
     let mut result = conn.query_iter(query).await.map_err(|e| e.to_string())?;
     let rows: Vec<mysql_async::Row> = result.collect().await.map_err(|e| e.to_string())?;
     
@@ -543,22 +754,16 @@ pub async fn get_columns(db: String, table: String, state: State<'_, AppState>)
 
 
 #[tauri::command]
-pub async fn get_table_count(db: String, table: String, state: State<'_, AppState>) -> Result<u64, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_table_count(db: String, table: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<u64, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     let count: Option<u64> = conn.query_first(format!("SELECT COUNT(*) FROM `{}`.`{}`", db, table)).await.map_err(|e| e.to_string())?;
     Ok(count.unwrap_or(0))
 }
 
 #[tauri::command]
-pub async fn rename_table(db: String, table: String, new_name: String, new_db: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn rename_table(db: String, table: String, new_name: String, new_db: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let target_db = new_db.unwrap_or_else(|| db.clone());
@@ -568,21 +773,15 @@ pub async fn rename_table(db: String, table: String, new_name: String, new_db: O
 }
 
 #[tauri::command]
-pub async fn truncate_table(db: String, table: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn truncate_table(db: String, table: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     conn.query_drop(format!("TRUNCATE TABLE `{}`.`{}`", db, table)).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn copy_table(db: String, table: String, new_db: String, new_table: String, with_data: bool, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn copy_table(db: String, table: String, new_db: String, new_table: String, with_data: bool, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let create_query = format!("CREATE TABLE `{}`.`{}` LIKE `{}`.`{}`", new_db, new_table, db, table);
@@ -597,11 +796,8 @@ pub async fn copy_table(db: String, table: String, new_db: String, new_table: St
 }
 
 #[tauri::command]
-pub async fn table_maintenance(db: String, table: String, op: String, state: State<'_, AppState>) -> Result<Vec<Vec<String>>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn table_maintenance(db: String, table: String, op: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<Vec<String>>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let valid_ops = ["ANALYZE", "OPTIMIZE", "CHECK", "CHECKSUM", "REPAIR", "FLUSH"];
@@ -684,11 +880,8 @@ impl ColumnDefinition {
 }
 
 #[tauri::command]
-pub async fn add_column(db: String, table: String, col: ColumnDefinition, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn add_column(db: String, table: String, col: ColumnDefinition, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let mut sql = format!("ALTER TABLE `{}`.`{}` ADD COLUMN {}", db, table, col.to_sql());
@@ -712,11 +905,8 @@ pub async fn add_column(db: String, table: String, col: ColumnDefinition, state:
 }
 
 #[tauri::command]
-pub async fn modify_column(db: String, table: String, col: ColumnDefinition, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn modify_column(db: String, table: String, col: ColumnDefinition, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let original = col.original_name.clone().ok_or("Original name required for modify")?;
@@ -735,11 +925,8 @@ pub async fn modify_column(db: String, table: String, col: ColumnDefinition, sta
 }
 
 #[tauri::command]
-pub async fn drop_column(db: String, table: String, column: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_column(db: String, table: String, column: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
 
@@ -747,73 +934,101 @@ pub async fn drop_column(db: String, table: String, column: String, state: State
     conn.query_drop(sql).await.map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-pub async fn insert_rows(db: String, table: String, rows: Vec<HashMap<String, String>>, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+/// Placeholders per statement are capped by the protocol's 16-bit parameter count
+const MAX_PLACEHOLDERS: usize = 65_535;
 
+/// Inserts `rows`, see `insert_statement` for how missing and "NULL" values are handled.
+#[tauri::command]
+pub async fn insert_rows(db: String, table: String, rows: Vec<HashMap<String, serde_json::Value>>, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
     if rows.is_empty() {
         return Ok(());
     }
 
-    // Assume all rows have the same keys, taken from the first row
-    let columns: Vec<String> = rows[0].keys().cloned().collect();
-    let col_list = columns.iter().map(|c| format!("`{}`", c)).collect::<Vec<_>>().join(", ");
+    let mut conn = state.get_conn(session_id.as_deref()).await?;
+    let table_columns = load_columns(&mut conn, &db, &table).await?;
 
-    let mut values_parts: Vec<String> = Vec::new();
-    
-    for row in rows {
-        let mut row_vals: Vec<String> = Vec::new();
-        for col in &columns {
-             if let Some(val) = row.get(col) {
-                 if val.to_uppercase() == "NULL" {
-                     row_vals.push("NULL".to_string());
-                 } else {
-                     // Basic escaping - for production a prepared statement loop is safer but this matches the current query architecture
-                     row_vals.push(format!("'{}'", val.replace("'", "''").replace("\\", "\\\\")));
-                 }
-             } else {
-                 row_vals.push("NULL".to_string());
-             }
-        }
-        values_parts.push(format!("({})", row_vals.join(", ")));
+    let width = insert_columns(&rows, &table_columns)?.len();
+    for chunk in rows.chunks((MAX_PLACEHOLDERS / width).max(1)) {
+        let (sql, params) = insert_statement(&db, &table, chunk, &table_columns)?;
+        conn.exec_drop(sql, params).await.map_err(|e| e.to_string())?;
     }
-    
-    let sql = format!(
-        "INSERT INTO `{}`.`{}` ({}) VALUES {}", 
-        db, table, col_list, values_parts.join(", ")
-    );
 
-    conn.query_drop(sql).await.map_err(|e| e.to_string())
+    Ok(())
 }
 
+/// Deletes one row per key. Keys are run one at a time so that a full-row key matching
+/// duplicates removes a single copy.
 #[tauri::command]
-pub async fn delete_rows(db: String, table: String, primary_key: String, ids: Vec<String>, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
-
-    if ids.is_empty() {
-        return Ok(());
+pub async fn delete_rows(db: String, table: String, keys: Vec<HashMap<String, serde_json::Value>>, session_id: Option<String>, state: State<'_, AppState>) -> Result<EditResult, String> {
+    let mut conn = state.get_conn(session_id.as_deref()).await?;
+    let columns = load_columns(&mut conn, &db, &table).await?;
+
+    let mut deleted = 0;
+    for key in &keys {
+        let (sql, params) = delete_statement(&db, &table, key, &columns)?;
+        conn.exec_drop(sql, params).await.map_err(|e| e.to_string())?;
+        deleted += conn.affected_rows();
     }
 
-    // Escape IDs to prevent injection
-    // Assuming IDs are string representations. 
-    // If integer ids are sent as strings, this is safe. 
-    // If strings with quotes, we replace.
-    let escaped_ids: Vec<String> = ids.iter()
-        .map(|id| format!("'{}'", id.replace("'", "''")))
-        .collect();
-        
-    let id_list = escaped_ids.join(", ");
-    
-    let sql = format!("DELETE FROM `{}`.`{}` WHERE `{}` IN ({})", db, table, primary_key, id_list);
-    
-    conn.query_drop(sql).await.map_err(|e| e.to_string())
+    Ok(EditResult { matched: deleted, affected: deleted, conflict: None })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::column;
+    use mysql_async::Value;
+    use serde_json::json;
+
+    fn row(entries: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    fn columns() -> Vec<ColumnInfo> {
+        vec![column("id", "int(11)"), column("name", "varchar(50)"), column("doc", "json")]
+    }
+
+    #[test]
+    fn builds_inserts_with_defaults_and_nulls() {
+        let rows = vec![
+            row(&[("id", json!(1))]),
+            row(&[("NAME", json!("null"))]),
+            row(&[("name", json!("b`c"))]),
+        ];
+        let (sql, params) = insert_statement("shop", "order`s", &rows, &columns()).unwrap();
+        assert_eq!(sql, "INSERT INTO `shop`.`order``s` (`id`, `name`) VALUES (?, DEFAULT), (DEFAULT, NULL), (DEFAULT, ?)");
+        assert_eq!(params, vec![Value::Int(1), Value::Bytes(b"b`c".to_vec())]);
+    }
+
+    #[test]
+    fn builds_updates_against_the_loaded_values() {
+        let values = row(&[("name", json!("new"))]);
+        let key = row(&[("id", json!(7))]);
+        let (sql, params) = update_statement("shop", "users", &values, &key, None, &columns()).unwrap();
+        assert_eq!(sql, "UPDATE `shop`.`users` SET `name` = ? WHERE `id` <=> ? LIMIT 1");
+        assert_eq!(params, vec![Value::Bytes(b"new".to_vec()), Value::Int(7)]);
+
+        // The original values join the key, so a changed row matches nothing
+        let original = row(&[("id", json!(7))]);
+        let (sql, _) = update_statement("shop", "users", &values, &row(&[("doc", json!({"a": 1}))]), Some(&original), &columns()).unwrap();
+        assert!(sql.starts_with("UPDATE `shop`.`users` SET `name` = ? WHERE "));
+        assert!(sql.contains("`id` <=> ?") && sql.contains("`doc` <=> CAST(? AS JSON)"), "{}", sql);
+    }
+
+    #[test]
+    fn builds_deletes_by_key() {
+        let (sql, params) = delete_statement("shop", "users", &row(&[("ID", json!(null))]), &columns()).unwrap();
+        assert_eq!(sql, "DELETE FROM `shop`.`users` WHERE `id` <=> ? LIMIT 1");
+        assert_eq!(params, vec![Value::NULL]);
+    }
+
+    #[test]
+    fn rejects_unknown_columns_and_empty_keys() {
+        let values = row(&[("name", json!("x"))]);
+        assert_eq!(delete_statement("shop", "users", &HashMap::new(), &columns()).unwrap_err(), "Row key is empty");
+        assert!(delete_statement("shop", "users", &row(&[("id` OR 1=1 -- ", json!(1))]), &columns()).is_err());
+        assert!(update_statement("shop", "users", &row(&[("nope", json!(1))]), &row(&[("id", json!(1))]), None, &columns()).is_err());
+        assert!(insert_statement("shop", "users", &[row(&[("nope", json!(1))])], &columns()).is_err());
+        assert!(update_statement("shop", "users", &values, &row(&[("id", json!("abc"))]), None, &columns()).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+//! Fixtures shared by the command tests.
+
+use crate::commands::table::ColumnInfo;
+
+/// A nullable column as `SHOW FULL COLUMNS` would describe it, e.g. `column("id", "int(11)")`.
+pub fn column(field: &str, data_type: &str) -> ColumnInfo {
+    ColumnInfo {
+        field: field.to_string(),
+        data_type: data_type.to_string(),
+        collation: None,
+        null: "YES".to_string(),
+        key: String::new(),
+        default: None,
+        extra: String::new(),
+    }
+}
@@ -0,0 +1,138 @@
+use tauri::State;
+use crate::state::{AppState, PinnedTransaction};
+use mysql_async::prelude::*;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// A transaction left untouched this long gets an idle warning.
+const IDLE_WARNING_SECS: u64 = 60;
+
+#[derive(Serialize)]
+pub struct TransactionStatus {
+    pub active: bool,
+    pub started_at: Option<String>,
+    pub idle_secs: u64,
+    /// Row locks held, from information_schema.INNODB_TRX
+    pub rows_locked: u64,
+    pub tables_locked: u64,
+    pub warnings: Vec<String>,
+}
+
+/// Takes a connection out of the pool and opens a transaction on it. Until commit or
+/// rollback, queries and grid edits of the session run on that connection.
+#[tauri::command]
+pub async fn begin_transaction(isolation_level: Option<String>, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let session_id = state.resolve_session_id(session_id.as_deref())?;
+    let pool = {
+        let sessions = state.sessions.lock().unwrap();
+        let session = sessions.get(&session_id).ok_or_else(|| format!("Session '{}' is not connected", session_id))?;
+        if session.transaction.is_some() {
+            return Err("A transaction is already open on this session".to_string());
+        }
+        session.pool.clone()
+    };
+
+    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    if let Some(level) = isolation_level {
+        let level = match level.to_uppercase().replace('_', " ").as_str() {
+            "READ UNCOMMITTED" => "READ UNCOMMITTED",
+            "READ COMMITTED" => "READ COMMITTED",
+            "REPEATABLE READ" => "REPEATABLE READ",
+            "SERIALIZABLE" => "SERIALIZABLE",
+            _ => return Err(format!("Unknown isolation level: {}", level)),
+        };
+        conn.query_drop(format!("SET TRANSACTION ISOLATION LEVEL {}", level)).await.map_err(|e| e.to_string())?;
+    }
+    conn.query_drop("START TRANSACTION").await.map_err(|e| e.to_string())?;
+
+    let transaction = PinnedTransaction {
+        connection_id: conn.id(),
+        conn: Arc::new(tokio::sync::Mutex::new(conn)),
+        started_at: chrono::Local::now().to_rfc3339(),
+        last_activity: Arc::new(Mutex::new(Instant::now())),
+    };
+
+    let mut sessions = state.sessions.lock().unwrap();
+    let session = sessions.get_mut(&session_id).ok_or_else(|| format!("Session '{}' is not connected", session_id))?;
+    if session.transaction.is_some() {
+        // Lost a race with another begin; ours goes back to the pool and is reset there
+        return Err("A transaction is already open on this session".to_string());
+    }
+    session.transaction = Some(transaction);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn commit_transaction(session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    end_transaction("COMMIT", session_id, &state).await
+}
+
+#[tauri::command]
+pub async fn rollback_transaction(session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    end_transaction("ROLLBACK", session_id, &state).await
+}
+
+async fn end_transaction(statement: &str, session_id: Option<String>, state: &AppState) -> Result<(), String> {
+    let session_id = state.resolve_session_id(session_id.as_deref())?;
+    let transaction = {
+        let mut sessions = state.sessions.lock().unwrap();
+        let session = sessions.get_mut(&session_id).ok_or_else(|| format!("Session '{}' is not connected", session_id))?;
+        session.transaction.take().ok_or("No open transaction on this session")?
+    };
+
+    // Waits for a command still running on the connection. Either way the connection goes
+    // back to the pool afterwards, so a failed COMMIT cannot leave the session stuck.
+    let mut conn = transaction.conn.lock().await;
+    conn.query_drop(statement).await.map_err(|e| e.to_string())
+}
+
+/// Polled by the UI to warn about transactions left idle or holding locks.
+#[tauri::command]
+pub async fn get_transaction_status(session_id: Option<String>, state: State<'_, AppState>) -> Result<TransactionStatus, String> {
+    let session_id = state.resolve_session_id(session_id.as_deref())?;
+    let (pool, info) = {
+        let sessions = state.sessions.lock().unwrap();
+        let session = sessions.get(&session_id).ok_or_else(|| format!("Session '{}' is not connected", session_id))?;
+        let info = session.transaction.as_ref().map(|t| {
+            (t.connection_id, t.started_at.clone(), t.last_activity.lock().unwrap().elapsed().as_secs())
+        });
+        (session.pool.clone(), info)
+    };
+
+    let Some((connection_id, started_at, idle_secs)) = info else {
+        return Ok(TransactionStatus {
+            active: false,
+            started_at: None,
+            idle_secs: 0,
+            rows_locked: 0,
+            tables_locked: 0,
+            warnings: Vec::new(),
+        });
+    };
+
+    // Separate connection: the pinned one may be busy running a statement
+    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
+    let locks: Option<(u64, u64)> = conn.exec_first(
+        "SELECT trx_rows_locked, trx_tables_locked FROM information_schema.INNODB_TRX WHERE trx_mysql_thread_id = ?",
+        (connection_id,),
+    ).await.map_err(|e| e.to_string())?;
+    let (rows_locked, tables_locked) = locks.unwrap_or((0, 0));
+
+    let mut warnings = Vec::new();
+    if idle_secs >= IDLE_WARNING_SECS {
+        warnings.push(format!("Transaction has been idle for {}s", idle_secs));
+    }
+    if rows_locked > 0 || tables_locked > 0 {
+        warnings.push(format!("Transaction holds locks on {} row(s) in {} table(s)", rows_locked, tables_locked));
+    }
+
+    Ok(TransactionStatus {
+        active: true,
+        started_at: Some(started_at),
+        idle_secs,
+        rows_locked,
+        tables_locked,
+        warnings,
+    })
+}
@@ -22,11 +22,8 @@ pub struct EventInfo {
 }
 
 #[tauri::command]
-pub async fn get_triggers(db: String, state: State<'_, AppState>) -> Result<Vec<TriggerInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_triggers(db: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<TriggerInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     // Select from information_schema.TRIGGERS
@@ -54,12 +51,10 @@ pub async fn get_triggers(db: String, state: State<'_, AppState>) -> Result<Vec<
 pub async fn create_trigger(
     db: String, name: String, table: String, 
     time: String, event: String, statement: String, 
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     // USE db first
@@ -72,22 +67,16 @@ pub async fn create_trigger(
 }
 
 #[tauri::command]
-pub async fn drop_trigger(db: String, name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_trigger(db: String, name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     conn.query_drop(format!("DROP TRIGGER `{}`.`{}`", db, name)).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_events(db: String, state: State<'_, AppState>) -> Result<Vec<EventInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_events(db: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<EventInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let query = "SELECT EVENT_NAME, EVENT_TYPE, INTERVAL_VALUE, INTERVAL_FIELD, STATUS, STARTS, ENDS 
@@ -124,11 +113,8 @@ pub async fn get_events(db: String, state: State<'_, AppState>) -> Result<Vec<Ev
 }
 
 #[tauri::command]
-pub async fn drop_event(db: String, name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_event(db: String, name: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     conn.query_drop(format!("DROP EVENT `{}`.`{}`", db, name)).await.map_err(|e| e.to_string())
@@ -140,12 +126,10 @@ pub async fn create_event(
     schedule: String, 
     status: String,
     statement: String,
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     conn.query_drop(format!("USE `{}`", db)).await.map_err(|e| e.to_string())?;
@@ -17,11 +17,8 @@ pub struct UserGrant {
 }
 
 #[tauri::command]
-pub async fn get_users(state: State<'_, AppState>) -> Result<Vec<UserInfo>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_users(session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<UserInfo>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     // Try to get more details if available (MySQL 5.7+)
@@ -49,11 +46,8 @@ pub async fn get_users(state: State<'_, AppState>) -> Result<Vec<UserInfo>, Stri
 }
 
 #[tauri::command]
-pub async fn create_user(name: String, host: String, password: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn create_user(name: String, host: String, password: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     // Safety: Parameterized or carefully constructed string?
@@ -67,11 +61,8 @@ pub async fn create_user(name: String, host: String, password: String, state: St
 }
 
 #[tauri::command]
-pub async fn drop_user(name: String, host: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn drop_user(name: String, host: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let query = format!("DROP USER '{}'@'{}'", name, host);
@@ -79,11 +70,8 @@ pub async fn drop_user(name: String, host: String, state: State<'_, AppState>) -
 }
 
 #[tauri::command]
-pub async fn get_grants(name: String, host: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn get_grants(name: String, host: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     let query = format!("SHOW GRANTS FOR '{}'@'{}'", name, host);
@@ -104,11 +92,8 @@ pub async fn get_grants(name: String, host: String, state: State<'_, AppState>)
 }
 
 #[tauri::command]
-pub async fn change_password(name: String, host: String, password: String, state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn change_password(name: String, host: String, password: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     
     // MySQL 5.7.6+ uses ALTER USER
@@ -117,11 +102,8 @@ pub async fn change_password(name: String, host: String, password: String, state
 }
 
 #[tauri::command]
-pub async fn flush_privileges(state: State<'_, AppState>) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+pub async fn flush_privileges(session_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
     conn.query_drop("FLUSH PRIVILEGES").await.map_err(|e| e.to_string())
 }
@@ -132,8 +114,8 @@ pub struct PrivilegeMatrix {
 }
 
 #[tauri::command]
-pub async fn get_privilege_matrix(name: String, host: String, state: State<'_, AppState>) -> Result<PrivilegeMatrix, String> {
-    let grants = get_grants(name, host, state).await?;
+pub async fn get_privilege_matrix(name: String, host: String, session_id: Option<String>, state: State<'_, AppState>) -> Result<PrivilegeMatrix, String> {
+    let grants = get_grants(name, host, session_id, state).await?;
     
     let mut matrix = PrivilegeMatrix {
         global: Vec::new(),
@@ -171,12 +153,10 @@ pub async fn update_privilege(
     privilege: String, 
     level: String, 
     is_grant: bool, 
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let sql = if is_grant {
@@ -194,12 +174,10 @@ pub async fn rename_user(
     old_host: String,
     new_name: String,
     new_host: String,
+    session_id: Option<String>,
     state: State<'_, AppState>
 ) -> Result<(), String> {
-    let pool = {
-        let pool_guard = state.pool.lock().unwrap();
-        pool_guard.as_ref().cloned().ok_or("Not connected")?
-    };
+    let pool = state.get_pool(session_id.as_deref())?;
     let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
 
     let sql = format!("RENAME USER '{}'@'{}' TO '{}'@'{}'", old_name, old_host, new_name, new_host);
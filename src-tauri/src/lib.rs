@@ -1,7 +1,9 @@
-use std::sync::Mutex;
-
-
 pub mod state;
+pub mod ssh_tunnel;
+pub mod secret_store;
+pub mod server_profiles;
+pub mod query_history;
+pub mod sql_splitter;
 pub mod commands;
 
 use state::AppState;
@@ -10,15 +12,18 @@ use state::AppState;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
-        .manage(AppState {
-            pool: Mutex::new(None),
-        })
+        .manage(AppState::new())
         .invoke_handler(tauri::generate_handler![
             // Server
             commands::server::connect_db, 
+            commands::server::list_sessions,
+            commands::server::switch_session,
+            commands::server::disconnect_session,
             commands::server::get_saved_servers,
             commands::server::save_server,
             commands::server::delete_server,
+            commands::server::export_server_profiles,
+            commands::server::import_server_profiles,
             commands::server::get_server_info,
             commands::server::get_process_list,
             commands::server::get_status_variables,
@@ -59,13 +64,21 @@ pub fn run() {
             commands::table::drop_column,
             commands::table::insert_rows,
             commands::table::delete_rows,
+            commands::changeset::apply_changeset,
 
             // Query
-            commands::server::get_saved_servers_local,
-            commands::server::save_server_local,
-            commands::server::delete_server_local,
+            commands::credentials::get_vault_status,
+            commands::credentials::unlock_vault,
+            commands::credentials::lock_vault,
             commands::query::execute_query,
             commands::query::execute_query_html,
+            commands::query::execute_query_stream,
+            commands::query::cancel_query,
+            commands::explain::explain_query_plan,
+            commands::transaction::begin_transaction,
+            commands::transaction::commit_transaction,
+            commands::transaction::rollback_transaction,
+            commands::transaction::get_transaction_status,
 
             // Import/Export
             commands::import_export::export_database,
@@ -118,6 +131,14 @@ pub fn run() {
             // Preferences
             commands::preferences::load_preferences,
             commands::preferences::save_preferences,
+
+            // Query History
+            commands::history::search_query_history,
+            commands::history::pin_query_history,
+            commands::history::delete_query_history,
+            commands::history::clear_query_history,
+            commands::history::get_history_retention,
+            commands::history::set_history_retention,
             // AI
             commands::ai::generate_sql,
             commands::ai::explain_query,
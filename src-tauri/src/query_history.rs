@@ -0,0 +1,347 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const HISTORY_FILE: &str = "query_history.jsonl";
+/// Extra entries tolerated past the limit before the file is compacted, so the
+/// append-only file is not rewritten on every query.
+const COMPACT_SLACK: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub sql: String,
+    /// RFC 3339. New entries are UTC like the frontend's `toISOString()`, but older ones
+    /// carry a local offset, so compare them with `parse_timestamp` rather than as text.
+    pub timestamp: String,
+    pub server_id: Option<String>,
+    pub database: Option<String>,
+    pub duration_ms: f64,
+    pub rows_affected: u64,
+    pub rows_returned: u64,
+    pub success: bool,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// How much history to keep. Pinned entries are never pruned.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryRetention {
+    pub max_entries: usize,
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: 5000,
+            max_age_days: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct HistoryFilter {
+    /// Whitespace-separated terms, all of which must appear in the SQL, database or error
+    pub text: Option<String>,
+    pub server_id: Option<String>,
+    pub database: Option<String>,
+    pub pinned_only: Option<bool>,
+    pub success: Option<bool>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Timestamp for a new entry, in the same UTC form as JavaScript's `toISOString()`.
+pub fn now_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Parses an entry timestamp whatever offset it was written with.
+pub fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// Query history kept in an append-only JSONL file, one entry per line.
+pub struct QueryHistory {
+    path: PathBuf,
+    entries: Vec<HistoryEntry>,
+    retention: HistoryRetention,
+}
+
+impl QueryHistory {
+    pub fn open(config_dir: &Path, retention: HistoryRetention) -> Result<Self, String> {
+        let path = config_dir.join(HISTORY_FILE);
+        let mut entries = Vec::new();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            // A line cut short by a crash is skipped rather than losing the whole history
+            entries.extend(content.lines().filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok()));
+        }
+
+        let mut history = Self { path, entries, retention };
+        if history.prune() {
+            history.rewrite()?;
+        }
+        Ok(history)
+    }
+
+    pub fn record(&mut self, entry: HistoryEntry) -> Result<(), String> {
+        let line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+        self.entries.push(entry);
+
+        if self.entries.len() > self.retention.max_entries + COMPACT_SLACK && self.prune() {
+            return self.rewrite();
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Merges in entries carried over from the old preferences.json history. Entries whose
+    /// SQL and timestamp are already in the store are skipped, so an import that is repeated
+    /// after preferences.json could not be rewritten adds nothing twice.
+    pub fn import(&mut self, entries: Vec<HistoryEntry>) -> Result<(), String> {
+        let known: HashSet<(&str, &str)> = self.entries.iter().map(|e| (e.sql.as_str(), e.timestamp.as_str())).collect();
+        let entries: Vec<HistoryEntry> = entries
+            .into_iter()
+            .filter(|e| !known.contains(&(e.sql.as_str(), e.timestamp.as_str())))
+            .collect();
+        self.entries.splice(0..0, entries);
+        // Stable, so entries with an unreadable timestamp keep their relative order up front
+        self.entries.sort_by_key(|e| parse_timestamp(&e.timestamp));
+        self.prune();
+        self.rewrite()
+    }
+
+    /// Newest first.
+    pub fn search(&self, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+        let terms: Vec<String> = filter
+            .text
+            .as_deref()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .collect();
+
+        self.entries
+            .iter()
+            .rev()
+            .filter(|e| filter.server_id.as_ref().map_or(true, |id| e.server_id.as_ref() == Some(id)))
+            .filter(|e| filter.database.as_ref().map_or(true, |db| e.database.as_ref() == Some(db)))
+            .filter(|e| !filter.pinned_only.unwrap_or(false) || e.pinned)
+            .filter(|e| filter.success.map_or(true, |s| e.success == s))
+            .filter(|e| {
+                if terms.is_empty() {
+                    return true;
+                }
+                let haystack = format!(
+                    "{}\n{}\n{}",
+                    e.sql,
+                    e.database.as_deref().unwrap_or(""),
+                    e.error.as_deref().unwrap_or("")
+                )
+                .to_lowercase();
+                terms.iter().all(|t| haystack.contains(t))
+            })
+            .skip(filter.offset.unwrap_or(0))
+            .take(filter.limit.unwrap_or(100))
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_pinned(&mut self, id: &str, pinned: bool) -> Result<(), String> {
+        let entry = self.entries.iter_mut().find(|e| e.id == id).ok_or("History entry not found")?;
+        entry.pinned = pinned;
+        self.rewrite()
+    }
+
+    pub fn delete(&mut self, id: &str) -> Result<(), String> {
+        self.entries.retain(|e| e.id != id);
+        self.rewrite()
+    }
+
+    /// Removes unpinned entries, optionally only those of one server.
+    pub fn clear(&mut self, server_id: Option<&str>) -> Result<(), String> {
+        self.entries
+            .retain(|e| e.pinned || server_id.is_some_and(|id| e.server_id.as_deref() != Some(id)));
+        self.rewrite()
+    }
+
+    pub fn retention(&self) -> &HistoryRetention {
+        &self.retention
+    }
+
+    pub fn set_retention(&mut self, retention: HistoryRetention) -> Result<(), String> {
+        self.retention = retention;
+        if self.prune() {
+            self.rewrite()?;
+        }
+        Ok(())
+    }
+
+    /// Applies the retention rules. Returns `true` if anything was dropped.
+    fn prune(&mut self) -> bool {
+        let before = self.entries.len();
+
+        if let Some(days) = self.retention.max_age_days {
+            let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+            // Entries whose age cannot be told are kept
+            self.entries
+                .retain(|e| e.pinned || parse_timestamp(&e.timestamp).map_or(true, |t| t >= cutoff));
+        }
+
+        let unpinned = self.entries.iter().filter(|e| !e.pinned).count();
+        let mut excess = unpinned.saturating_sub(self.retention.max_entries);
+        if excess > 0 {
+            // Oldest first in the file, so drop from the front
+            self.entries.retain(|e| {
+                if excess > 0 && !e.pinned {
+                    excess -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        self.entries.len() != before
+    }
+
+    fn rewrite(&self) -> Result<(), String> {
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+            content.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn entry(id: &str, timestamp: String) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            sql: format!("SELECT {}", id),
+            timestamp,
+            server_id: None,
+            database: None,
+            duration_ms: 0.0,
+            rows_affected: 0,
+            rows_returned: 0,
+            success: true,
+            error: None,
+            pinned: false,
+        }
+    }
+
+    fn at_offset(time: DateTime<Utc>, hours: i32) -> String {
+        time.with_timezone(&FixedOffset::east_opt(hours * 3600).unwrap()).to_rfc3339()
+    }
+
+    fn temp_history(retention: HistoryRetention) -> (PathBuf, QueryHistory) {
+        let dir = std::env::temp_dir().join(format!("omnimin-history-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let history = QueryHistory::open(&dir, retention).unwrap();
+        (dir, history)
+    }
+
+    fn ids(history: &QueryHistory) -> Vec<&str> {
+        history.entries.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn new_timestamps_match_the_frontend_format() {
+        let ts = now_timestamp();
+        assert!(ts.ends_with('Z'), "{}", ts);
+        assert!(parse_timestamp(&ts).is_some());
+        assert!(parse_timestamp("not a date").is_none());
+    }
+
+    #[test]
+    fn import_orders_mixed_offsets_chronologically() {
+        let (dir, mut history) = temp_history(HistoryRetention::default());
+        // 05:00Z written at +05:00 sorts after 06:00Z as text, but is an hour earlier
+        history
+            .import(vec![
+                entry("later", "2026-01-01T06:00:00.000Z".to_string()),
+                entry("earlier", "2026-01-01T10:00:00+05:00".to_string()),
+            ])
+            .unwrap();
+        assert_eq!(ids(&history), vec!["earlier", "later"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn repeated_import_adds_nothing_twice() {
+        let (dir, mut history) = temp_history(HistoryRetention::default());
+        let legacy = || vec![entry("a", "2026-01-01T06:00:00.000Z".to_string()), entry("b", "2026-01-01T07:00:00.000Z".to_string())];
+        history.import(legacy()).unwrap();
+        // Same legacy list again, as after a failed preferences write; ids are new each time
+        let mut again = legacy();
+        again.iter_mut().for_each(|e| e.id.push_str("-retry"));
+        history.import(again).unwrap();
+        assert_eq!(ids(&history), vec!["a", "b"]);
+
+        let reopened = QueryHistory::open(&dir, history.retention().clone()).unwrap();
+        assert_eq!(ids(&reopened), vec!["a", "b"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prunes_by_age_across_offsets() {
+        let (dir, mut history) = temp_history(HistoryRetention::default());
+        let now = Utc::now();
+        history
+            .import(vec![
+                // 30 hours old, but its +10:00 wall clock reads as 20 hours ago
+                entry("old", at_offset(now - chrono::Duration::hours(30), 10)),
+                // 20 hours old, but its -08:00 wall clock reads as 28 hours ago
+                entry("recent", at_offset(now - chrono::Duration::hours(20), -8)),
+                entry("utc", now_timestamp()),
+            ])
+            .unwrap();
+
+        history
+            .set_retention(HistoryRetention {
+                max_entries: 100,
+                max_age_days: Some(1),
+            })
+            .unwrap();
+        assert_eq!(ids(&history), vec!["recent", "utc"]);
+
+        // The rewritten file holds the same entries
+        let reopened = QueryHistory::open(&dir, history.retention().clone()).unwrap();
+        assert_eq!(ids(&reopened), vec!["recent", "utc"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keeps_pinned_and_unreadable_entries_when_pruning_by_age() {
+        let (dir, mut history) = temp_history(HistoryRetention::default());
+        let mut pinned = entry("pinned", "2000-01-01T00:00:00Z".to_string());
+        pinned.pinned = true;
+        history
+            .import(vec![pinned, entry("unknown", "yesterday".to_string()), entry("stale", "2000-01-02T00:00:00Z".to_string())])
+            .unwrap();
+
+        history
+            .set_retention(HistoryRetention {
+                max_entries: 100,
+                max_age_days: Some(7),
+            })
+            .unwrap();
+        assert_eq!(ids(&history), vec!["unknown", "pinned"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
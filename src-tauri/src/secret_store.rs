@@ -0,0 +1,291 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 2 binds every entry to its id through the AEAD associated data
+const VAULT_VERSION: u32 = 2;
+const VERIFIER_PLAINTEXT: &[u8] = b"omnimin-credential-vault";
+
+/// Secret fields of a saved server. They never touch `servers.json`.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct ServerSecrets {
+    pub pass: Option<String>,
+    pub ssh_pass: Option<String>,
+    pub ssh_key_passphrase: Option<String>,
+}
+
+impl ServerSecrets {
+    pub fn is_empty(&self) -> bool {
+        self.pass.is_none() && self.ssh_pass.is_none() && self.ssh_key_passphrase.is_none()
+    }
+
+    /// Overlays the fields that are set in `other`. `None` means "unchanged".
+    pub fn merge(&mut self, other: ServerSecrets) {
+        if other.pass.is_some() {
+            self.pass = other.pass;
+        }
+        if other.ssh_pass.is_some() {
+            self.ssh_pass = other.ssh_pass;
+        }
+        if other.ssh_key_passphrase.is_some() {
+            self.ssh_key_passphrase = other.ssh_key_passphrase;
+        }
+    }
+}
+
+/// Implemented by saved-server types so their secrets can be split off before writing to disk.
+pub trait HasSecrets {
+    fn server_id(&self) -> &str;
+    /// Moves the secret fields out, leaving them `None`.
+    fn take_secrets(&mut self) -> ServerSecrets;
+    fn set_secrets(&mut self, secrets: ServerSecrets);
+}
+
+/// Backend holding saved-server secrets, keyed by server id.
+pub trait SecretStore: Send {
+    fn get(&self, id: &str) -> Result<Option<ServerSecrets>, String>;
+    fn put(&mut self, id: &str, secrets: &ServerSecrets) -> Result<(), String>;
+    fn remove(&mut self, id: &str) -> Result<(), String>;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SealedBox {
+    nonce: String,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: String,
+    salt: String,
+    verifier: SealedBox,
+    entries: HashMap<String, SealedBox>,
+}
+
+/// File-based store: every entry is sealed with AES-256-GCM under a key
+/// derived from the master password with Argon2id.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    file: VaultFile,
+}
+
+impl EncryptedFileStore {
+    pub fn exists(path: &Path) -> bool {
+        path.exists()
+    }
+
+    /// Opens the vault at `path`, creating it with `master_password` if it does not exist yet.
+    pub fn open(path: &Path, master_password: &str) -> Result<Self, String> {
+        if master_password.is_empty() {
+            return Err("Master password cannot be empty".to_string());
+        }
+
+        if !path.exists() {
+            let mut salt = [0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            let cipher = derive_cipher(master_password, &salt)?;
+            let store = Self {
+                path: path.to_path_buf(),
+                file: VaultFile {
+                    version: VAULT_VERSION,
+                    kdf: "argon2id".to_string(),
+                    salt: general_purpose::STANDARD.encode(salt),
+                    verifier: seal(&cipher, VERIFIER_PLAINTEXT, b"")?,
+                    entries: HashMap::new(),
+                },
+                cipher,
+            };
+            store.save()?;
+            return Ok(store);
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut file: VaultFile = serde_json::from_str(&content).map_err(|e| format!("Corrupted credential vault: {}", e))?;
+        if file.version > VAULT_VERSION {
+            return Err(format!("Credential vault version {} is newer than this app supports", file.version));
+        }
+
+        let salt = general_purpose::STANDARD.decode(&file.salt).map_err(|e| e.to_string())?;
+        let cipher = derive_cipher(master_password, &salt)?;
+        match open_sealed(&cipher, &file.verifier, b"") {
+            Ok(plain) if plain == VERIFIER_PLAINTEXT => {}
+            _ => return Err("Wrong master password".to_string()),
+        }
+
+        let upgrade = file.version < VAULT_VERSION;
+        if upgrade {
+            // Version 1 sealed entries without associated data; reseal them bound to their id
+            for (id, sealed) in file.entries.iter_mut() {
+                let plain = open_sealed(&cipher, sealed, b"")?;
+                *sealed = seal(&cipher, &plain, &entry_aad(id))?;
+            }
+            file.version = VAULT_VERSION;
+        }
+
+        let store = Self {
+            path: path.to_path_buf(),
+            cipher,
+            file,
+        };
+        if upgrade {
+            store.save()?;
+        }
+        Ok(store)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(&self.file).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn get(&self, id: &str) -> Result<Option<ServerSecrets>, String> {
+        match self.file.entries.get(id) {
+            Some(sealed) => {
+                let plain = open_sealed(&self.cipher, sealed, &entry_aad(id))?;
+                serde_json::from_slice(&plain).map(Some).map_err(|e| e.to_string())
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn put(&mut self, id: &str, secrets: &ServerSecrets) -> Result<(), String> {
+        let plain = serde_json::to_vec(secrets).map_err(|e| e.to_string())?;
+        let sealed = seal(&self.cipher, &plain, &entry_aad(id))?;
+        self.file.entries.insert(id.to_string(), sealed);
+        self.save()
+    }
+
+    fn remove(&mut self, id: &str) -> Result<(), String> {
+        if self.file.entries.remove(id).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+}
+
+fn derive_cipher(master_password: &str, salt: &[u8]) -> Result<Aes256Gcm, String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(master_password.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+/// Associated data for an entry, so a sealed box only opens under the id it was written for.
+fn entry_aad(id: &str) -> Vec<u8> {
+    format!("entry:{}", id).into_bytes()
+}
+
+fn seal(cipher: &Aes256Gcm, plain: &[u8], aad: &[u8]) -> Result<SealedBox, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let data = cipher.encrypt(&nonce, Payload { msg: plain, aad }).map_err(|_| "Encryption failed".to_string())?;
+    Ok(SealedBox {
+        nonce: general_purpose::STANDARD.encode(nonce),
+        data: general_purpose::STANDARD.encode(data),
+    })
+}
+
+fn open_sealed(cipher: &Aes256Gcm, sealed: &SealedBox, aad: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = general_purpose::STANDARD.decode(&sealed.nonce).map_err(|e| e.to_string())?;
+    let data = general_purpose::STANDARD.decode(&sealed.data).map_err(|e| e.to_string())?;
+    if nonce.len() != 12 {
+        return Err("Corrupted credential entry".to_string());
+    }
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: &data, aad })
+        .map_err(|_| "Failed to decrypt credentials".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault() -> PathBuf {
+        std::env::temp_dir().join(format!("omnimin-vault-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    fn secrets(pass: &str) -> ServerSecrets {
+        ServerSecrets {
+            pass: Some(pass.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_entries_across_reopen() {
+        let path = temp_vault();
+        let mut store = EncryptedFileStore::open(&path, "master").unwrap();
+        store.put("a", &secrets("secret-a")).unwrap();
+        store.put("b", &secrets("secret-b")).unwrap();
+
+        let reopened = EncryptedFileStore::open(&path, "master").unwrap();
+        assert_eq!(reopened.get("a").unwrap(), Some(secrets("secret-a")));
+        assert_eq!(reopened.get("b").unwrap(), Some(secrets("secret-b")));
+        assert_eq!(reopened.get("missing").unwrap(), None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rejects_wrong_and_empty_password() {
+        let path = temp_vault();
+        EncryptedFileStore::open(&path, "master").unwrap();
+        assert_eq!(EncryptedFileStore::open(&path, "other").err().as_deref(), Some("Wrong master password"));
+        assert!(EncryptedFileStore::open(&path, "").is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn swapped_entries_do_not_decrypt() {
+        let path = temp_vault();
+        let mut store = EncryptedFileStore::open(&path, "master").unwrap();
+        store.put("a", &secrets("secret-a")).unwrap();
+        store.put("b", &secrets("secret-b")).unwrap();
+
+        let a = store.file.entries["a"].clone();
+        store.file.entries.insert("b".to_string(), a);
+        assert!(store.get("b").is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn upgrades_version_1_entries() {
+        let path = temp_vault();
+        let mut store = EncryptedFileStore::open(&path, "master").unwrap();
+        let plain = serde_json::to_vec(&secrets("legacy")).unwrap();
+        store.file.version = 1;
+        store.file.entries.insert("old".to_string(), seal(&store.cipher, &plain, b"").unwrap());
+        store.save().unwrap();
+
+        let upgraded = EncryptedFileStore::open(&path, "master").unwrap();
+        assert_eq!(upgraded.file.version, VAULT_VERSION);
+        assert_eq!(upgraded.get("old").unwrap(), Some(secrets("legacy")));
+        let on_disk = EncryptedFileStore::open(&path, "master").unwrap();
+        assert_eq!(on_disk.get("old").unwrap(), Some(secrets("legacy")));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn merge_keeps_unset_fields() {
+        let mut current = ServerSecrets {
+            pass: Some("db".to_string()),
+            ssh_pass: Some("ssh".to_string()),
+            ssh_key_passphrase: None,
+        };
+        current.merge(ServerSecrets {
+            ssh_pass: Some("new-ssh".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(current.pass.as_deref(), Some("db"));
+        assert_eq!(current.ssh_pass.as_deref(), Some("new-ssh"));
+    }
+}
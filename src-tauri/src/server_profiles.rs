@@ -0,0 +1,169 @@
+use crate::secret_store::{HasSecrets, ServerSecrets};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const SERVERS_FILE: &str = "servers.json";
+/// Bump when the on-disk layout changes and add a step to `parse`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Dev,
+    Staging,
+    Prod,
+}
+
+/// A saved server connection.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ServerProfile {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub pass: Option<String>,
+    pub ssl: Option<bool>,
+    pub ssl_mode: Option<String>, // "disabled" | "required" | "verify_ca" | "verify_identity"
+    pub ssl_ca_path: Option<String>,
+    pub ssl_cert_path: Option<String>,
+    pub ssl_key_path: Option<String>,
+    pub ssl_skip_verify: Option<bool>,
+    pub ssh_enabled: Option<bool>,
+    pub ssh_host: Option<String>,
+    pub ssh_port: Option<u16>,
+    pub ssh_user: Option<String>,
+    pub ssh_pass: Option<String>,
+    pub ssh_key_path: Option<String>,
+    pub ssh_key_passphrase: Option<String>,
+    pub ssh_known_hosts_path: Option<String>,
+    pub ssh_strict_host_key: Option<bool>,
+    pub database: Option<String>,
+    pub socket: Option<String>,
+    pub connect_timeout: Option<u64>,
+    pub compression_level: Option<u32>,
+    pub auto_connect: Option<bool>,
+    /// Folder path in the server list, segments separated by `/`
+    pub folder: Option<String>,
+    /// Colour tag, any CSS colour
+    pub color: Option<String>,
+    pub environment: Option<Environment>,
+}
+
+impl HasSecrets for ServerProfile {
+    fn server_id(&self) -> &str {
+        &self.id
+    }
+
+    fn take_secrets(&mut self) -> ServerSecrets {
+        ServerSecrets {
+            pass: self.pass.take(),
+            ssh_pass: self.ssh_pass.take(),
+            ssh_key_passphrase: self.ssh_key_passphrase.take(),
+        }
+    }
+
+    fn set_secrets(&mut self, secrets: ServerSecrets) {
+        self.pass = secrets.pass;
+        self.ssh_pass = secrets.ssh_pass;
+        self.ssh_key_passphrase = secrets.ssh_key_passphrase;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProfilesFile {
+    version: u32,
+    profiles: Vec<ServerProfile>,
+}
+
+/// Every file layout `servers.json` has had.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredFile {
+    Versioned(ProfilesFile),
+    /// Before versioning the file was a bare array of servers
+    Legacy(Vec<ServerProfile>),
+}
+
+/// Reads and writes the saved-server list.
+pub struct ProfileRepository {
+    path: PathBuf,
+}
+
+impl ProfileRepository {
+    pub fn new(config_dir: &Path) -> Self {
+        Self {
+            path: config_dir.join(SERVERS_FILE),
+        }
+    }
+
+    /// Profiles as stored. Files written before the credential vault existed may still hold
+    /// plaintext secrets; they stay untouched until `unlock_vault` migrates them.
+    pub fn load(&self) -> Result<Vec<ServerProfile>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        parse(&content)
+    }
+
+    /// Profiles with secrets stripped, safe to hand to the UI.
+    pub fn list(&self) -> Result<Vec<ServerProfile>, String> {
+        let mut profiles = self.load()?;
+        for profile in profiles.iter_mut() {
+            profile.take_secrets();
+        }
+        Ok(profiles)
+    }
+
+    /// Inserts or replaces the profile with the same id.
+    pub fn upsert(&self, profile: ServerProfile) -> Result<Vec<ServerProfile>, String> {
+        let mut profiles = self.load()?;
+        match profiles.iter().position(|p| p.id == profile.id) {
+            Some(idx) => profiles[idx] = profile,
+            None => profiles.push(profile),
+        }
+        self.save(&profiles)?;
+        Ok(profiles)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<Vec<ServerProfile>, String> {
+        let mut profiles = self.load()?;
+        profiles.retain(|p| p.id != id);
+        self.save(&profiles)?;
+        Ok(profiles)
+    }
+
+    /// Writes the whole list atomically: a crash mid-write leaves the previous file intact.
+    pub fn save(&self, profiles: &[ServerProfile]) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = serialize(profiles)?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+/// Parses any known layout of the profiles file into the current one.
+pub fn parse(content: &str) -> Result<Vec<ServerProfile>, String> {
+    let stored: StoredFile = serde_json::from_str(content).map_err(|e| format!("Failed to parse server profiles: {}", e))?;
+    let (version, profiles) = match stored {
+        StoredFile::Versioned(file) => (file.version, file.profiles),
+        StoredFile::Legacy(profiles) => (0, profiles),
+    };
+    if version > SCHEMA_VERSION {
+        return Err(format!("Server profiles version {} is newer than this app supports", version));
+    }
+    // 0 -> 1 only added the envelope; fields added since default to None
+    Ok(profiles)
+}
+
+pub fn serialize(profiles: &[ServerProfile]) -> Result<String, String> {
+    let file = ProfilesFile {
+        version: SCHEMA_VERSION,
+        profiles: profiles.to_vec(),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| e.to_string())
+}
@@ -0,0 +1,692 @@
+use serde::Serialize;
+
+/// Object kinds whose CREATE/ALTER statement may carry a `BEGIN ... END` body
+const COMPOUND_KINDS: &[&str] = &["PROCEDURE", "FUNCTION", "TRIGGER", "EVENT"];
+/// Object kinds that settle a CREATE/ALTER statement as a plain one
+const PLAIN_KINDS: &[&str] = &[
+    "TABLE", "VIEW", "INDEX", "DATABASE", "SCHEMA", "USER", "ROLE", "TEMPORARY", "UNIQUE", "FULLTEXT",
+    "SPATIAL", "TABLESPACE", "SERVER", "SEQUENCE", "LOGFILE", "RESOURCE", "INSTANCE",
+];
+
+/// One statement of a script, without its delimiter and with `--`/`#` comments removed.
+/// Lines are 1-based; offsets are byte offsets into the script, end exclusive.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SqlStatement {
+    pub sql: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_offset: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Code,
+    BlockComment,
+    Quote(char),
+}
+
+/// Splits a script into statements the way the mysql client does: it honours the
+/// `DELIMITER` command, the `\G`/`\g` terminators, quotes and comments, and keeps the
+/// `BEGIN ... END` body of a routine, trigger or event together under the default `;`.
+///
+/// Input can be fed in chunks of any size; statements are returned as soon as they end.
+pub struct SqlSplitter {
+    delimiter: String,
+    /// Text after the last newline, held back until its line is complete
+    pending: String,
+    line: usize,
+    offset: usize,
+    mode: Mode,
+    /// Inside a `/*! ... */`, `/*M! ... */` or `/*+ ... */` comment, whose content is code
+    conditional: bool,
+
+    current: String,
+    has_code: bool,
+    start_line: usize,
+    end_line: usize,
+    start_offset: usize,
+    end_offset: usize,
+
+    word: String,
+    word_qualified: bool,
+    after_dot: bool,
+    words_seen: usize,
+    /// Whether `BEGIN`/`CASE`/`END` nest; `None` while a CREATE/ALTER has not named its object kind
+    compound: Option<bool>,
+    leading_begin: bool,
+    depth: usize,
+    /// An `END` was seen and the next word tells whether it closes a block (`END IF` does not)
+    pending_end: bool,
+}
+
+impl Default for SqlSplitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqlSplitter {
+    pub fn new() -> Self {
+        SqlSplitter {
+            delimiter: ";".to_string(),
+            pending: String::new(),
+            line: 1,
+            offset: 0,
+            mode: Mode::Code,
+            conditional: false,
+            current: String::new(),
+            has_code: false,
+            start_line: 0,
+            end_line: 0,
+            start_offset: 0,
+            end_offset: 0,
+            word: String::new(),
+            word_qualified: false,
+            after_dot: false,
+            words_seen: 0,
+            compound: None,
+            leading_begin: false,
+            depth: 0,
+            pending_end: false,
+        }
+    }
+
+    /// The delimiter currently in effect.
+    pub fn delimiter(&self) -> &str {
+        &self.delimiter
+    }
+
+    /// Line the splitter has read up to, 1-based.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn feed(&mut self, chunk: &str) -> Vec<SqlStatement> {
+        let mut out = Vec::new();
+        self.pending.push_str(chunk);
+        let Some(last) = self.pending.rfind('\n') else {
+            return out;
+        };
+        let complete: String = self.pending.drain(..=last).collect();
+        for line in complete.split_inclusive('\n') {
+            self.scan_line(line, &mut out);
+        }
+        out
+    }
+
+    /// Splits what is left after the last newline. The final statement may lack a delimiter.
+    pub fn finish(mut self) -> Vec<SqlStatement> {
+        let mut out = Vec::new();
+        let rest = std::mem::take(&mut self.pending);
+        self.scan_line(&rest, &mut out);
+        self.end_word();
+        out.extend(self.end_statement());
+        out
+    }
+
+    fn scan_line(&mut self, line: &str, out: &mut Vec<SqlStatement>) {
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap();
+            let pos = self.offset + i;
+
+            match self.mode {
+                Mode::Quote(q) => {
+                    self.push(c, pos, true);
+                    i += c.len_utf8();
+                    if c == '\\' && q != '`' {
+                        if let Some(escaped) = line[i..].chars().next() {
+                            self.push(escaped, self.offset + i, true);
+                            i += escaped.len_utf8();
+                        }
+                    } else if c == q {
+                        if line[i..].starts_with(q) {
+                            self.push(q, self.offset + i, true);
+                            i += q.len_utf8();
+                        } else {
+                            self.mode = Mode::Code;
+                        }
+                    }
+                    continue;
+                }
+                Mode::BlockComment => {
+                    if rest.starts_with("*/") {
+                        self.push_str("*/", pos, false);
+                        self.mode = Mode::Code;
+                        i += 2;
+                    } else {
+                        self.push(c, pos, false);
+                        i += c.len_utf8();
+                    }
+                    continue;
+                }
+                Mode::Code => {}
+            }
+
+            if self.conditional {
+                if rest.starts_with("*/") {
+                    self.end_word();
+                    self.push_str("*/", pos, true);
+                    self.conditional = false;
+                    i += 2;
+                    continue;
+                }
+            } else {
+                if rest.starts_with(self.delimiter.as_str()) {
+                    self.end_word();
+                    if self.pending_end {
+                        self.pending_end = false;
+                        self.depth = self.depth.saturating_sub(1);
+                    }
+                    let len = self.delimiter.len();
+                    if self.depth == 0 || self.delimiter != ";" {
+                        out.extend(self.end_statement());
+                    } else {
+                        self.push_str(";", pos, true);
+                    }
+                    i += len;
+                    continue;
+                }
+                if rest.starts_with("\\G") || rest.starts_with("\\g") {
+                    self.end_word();
+                    out.extend(self.end_statement());
+                    i += 2;
+                    continue;
+                }
+                if !self.has_code {
+                    if let Some(delimiter) = parse_delimiter_command(rest) {
+                        if !delimiter.is_empty() {
+                            self.delimiter = delimiter.to_string();
+                        }
+                        i = line.len();
+                        continue;
+                    }
+                }
+            }
+
+            if c == '#' || (rest.starts_with("--") && rest[2..].chars().next().map_or(true, char::is_whitespace)) {
+                // Keep the newline so the surrounding tokens stay apart
+                self.end_word();
+                i += rest.find('\n').unwrap_or(rest.len());
+                continue;
+            }
+
+            if rest.starts_with("/*") {
+                self.end_word();
+                let marker = ["/*!", "/*M!", "/*+"].into_iter().find(|m| rest.starts_with(m));
+                match marker {
+                    Some(marker) if !self.conditional => {
+                        // Version digits are not a word of the statement
+                        let digits = rest[marker.len()..].bytes().take_while(u8::is_ascii_digit).count();
+                        let len = marker.len() + digits;
+                        self.push_str(&rest[..len], pos, true);
+                        self.conditional = true;
+                        i += len;
+                    }
+                    _ => {
+                        self.push_str("/*", pos, false);
+                        self.mode = Mode::BlockComment;
+                        i += 2;
+                    }
+                }
+                continue;
+            }
+
+            if c == '\'' || c == '"' || c == '`' {
+                self.end_word();
+                self.after_dot = false;
+                self.push(c, pos, true);
+                self.mode = Mode::Quote(c);
+            } else if c.is_alphanumeric() || c == '_' || c == '$' {
+                if self.word.is_empty() {
+                    self.word_qualified = self.after_dot;
+                }
+                self.word.push(c);
+                self.push(c, pos, true);
+            } else {
+                self.end_word();
+                if !c.is_whitespace() {
+                    self.after_dot = c == '.';
+                }
+                self.push(c, pos, !c.is_whitespace());
+            }
+            i += c.len_utf8();
+        }
+
+        if line.ends_with('\n') {
+            self.line += 1;
+        }
+        self.offset += line.len();
+    }
+
+    fn push(&mut self, c: char, pos: usize, code: bool) {
+        let mut buf = [0; 4];
+        self.push_str(c.encode_utf8(&mut buf), pos, code);
+    }
+
+    /// Appends to the current statement. Whitespace and comments before its first token are dropped.
+    fn push_str(&mut self, s: &str, pos: usize, code: bool) {
+        if code && !self.has_code {
+            self.has_code = true;
+            self.start_line = self.line;
+            self.start_offset = pos;
+        }
+        if !self.has_code {
+            return;
+        }
+        self.current.push_str(s);
+        if !s.trim().is_empty() {
+            self.end_line = self.line;
+            self.end_offset = pos + s.len();
+        }
+    }
+
+    fn end_word(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+        let word = std::mem::take(&mut self.word).to_ascii_uppercase();
+        let qualified = std::mem::replace(&mut self.word_qualified, false);
+        self.after_dot = false;
+        self.words_seen += 1;
+
+        if self.words_seen == 1 {
+            self.leading_begin = word == "BEGIN";
+            self.compound = match word.as_str() {
+                "CREATE" | "ALTER" | "BEGIN" => None,
+                _ => Some(false),
+            };
+            return;
+        }
+        if qualified {
+            return;
+        }
+
+        match self.compound {
+            None if self.leading_begin => {
+                // Only MariaDB's `BEGIN NOT ATOMIC` opens a block; plain `BEGIN` starts a transaction
+                let block = word == "NOT";
+                self.compound = Some(block);
+                if block {
+                    self.depth = 1;
+                }
+                return;
+            }
+            None => {
+                if COMPOUND_KINDS.contains(&word.as_str()) {
+                    self.compound = Some(true);
+                } else if PLAIN_KINDS.contains(&word.as_str()) {
+                    self.compound = Some(false);
+                }
+                return;
+            }
+            Some(false) => return,
+            Some(true) => {}
+        }
+
+        if self.pending_end {
+            self.pending_end = false;
+            match word.as_str() {
+                "IF" | "LOOP" | "WHILE" | "REPEAT" => return,
+                "CASE" => {
+                    self.depth = self.depth.saturating_sub(1);
+                    return;
+                }
+                _ => self.depth = self.depth.saturating_sub(1),
+            }
+        }
+        match word.as_str() {
+            "BEGIN" | "CASE" => self.depth += 1,
+            "END" => self.pending_end = true,
+            _ => {}
+        }
+    }
+
+    fn end_statement(&mut self) -> Option<SqlStatement> {
+        let sql = std::mem::take(&mut self.current);
+        let statement = self.has_code.then(|| SqlStatement {
+            sql: sql.trim_end().to_string(),
+            start_line: self.start_line,
+            end_line: self.end_line,
+            start_offset: self.start_offset,
+            end_offset: self.end_offset,
+        });
+
+        self.has_code = false;
+        self.word.clear();
+        self.after_dot = false;
+        self.words_seen = 0;
+        self.compound = None;
+        self.leading_begin = false;
+        self.depth = 0;
+        self.pending_end = false;
+        statement
+    }
+}
+
+/// Returns the argument of a `DELIMITER` command starting at `text`.
+fn parse_delimiter_command(text: &str) -> Option<&str> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 10 || !bytes[..9].eq_ignore_ascii_case(b"delimiter") || !bytes[9].is_ascii_whitespace() {
+        return None;
+    }
+    Some(text[9..].split_whitespace().next().unwrap_or(""))
+}
+
+/// Splits a whole script at once.
+pub fn split_sql(sql: &str) -> Vec<SqlStatement> {
+    let mut splitter = SqlSplitter::new();
+    let mut statements = splitter.feed(sql);
+    statements.extend(splitter.finish());
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sql(script: &str) -> Vec<String> {
+        split_sql(script).into_iter().map(|s| s.sql).collect()
+    }
+
+    #[test]
+    fn splits_on_semicolons() {
+        assert_eq!(sql("SELECT 1; SELECT 2;\nSELECT 3"), ["SELECT 1", "SELECT 2", "SELECT 3"]);
+    }
+
+    #[test]
+    fn skips_empty_statements() {
+        assert_eq!(sql(";;  ;\n\nSELECT 1;;\n"), ["SELECT 1"]);
+        assert!(sql("").is_empty());
+        assert!(sql("  \n\t\n").is_empty());
+    }
+
+    #[test]
+    fn ignores_delimiters_in_quotes() {
+        assert_eq!(
+            sql("INSERT INTO t VALUES ('a;b', \"c;d\"); SELECT `x;y` FROM t;"),
+            ["INSERT INTO t VALUES ('a;b', \"c;d\")", "SELECT `x;y` FROM t"]
+        );
+    }
+
+    #[test]
+    fn handles_escaped_and_doubled_quotes() {
+        assert_eq!(
+            sql(r"SELECT 'it\'s;'; SELECT 'it''s;'; SELECT 'back\\'; SELECT `a``;b`;"),
+            [r"SELECT 'it\'s;'", "SELECT 'it''s;'", r"SELECT 'back\\'", "SELECT `a``;b`"]
+        );
+    }
+
+    #[test]
+    fn backslash_does_not_escape_in_backticks() {
+        assert_eq!(sql("SELECT `a\\`; SELECT 2;"), ["SELECT `a\\`", "SELECT 2"]);
+    }
+
+    #[test]
+    fn quotes_span_lines() {
+        assert_eq!(sql("INSERT INTO t VALUES ('line1;\nline2');\nSELECT 1;"), ["INSERT INTO t VALUES ('line1;\nline2')", "SELECT 1"]);
+    }
+
+    #[test]
+    fn strips_line_comments() {
+        assert_eq!(
+            sql("-- header; not a statement\nSELECT 1; # trailing; comment\n# another\nSELECT 2 -- end;\n;"),
+            ["SELECT 1", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn double_dash_needs_whitespace_to_start_a_comment() {
+        assert_eq!(sql("SELECT 1--1;\nSELECT 2;"), ["SELECT 1--1", "SELECT 2"]);
+        assert_eq!(sql("SELECT 1 --\nSELECT 2;"), ["SELECT 1 \nSELECT 2"]);
+    }
+
+    #[test]
+    fn comment_markers_inside_strings_are_text() {
+        assert_eq!(sql("SELECT '-- x', '# y', '/* z */';"), ["SELECT '-- x', '# y', '/* z */'"]);
+    }
+
+    #[test]
+    fn block_comments_before_a_statement_are_dropped() {
+        assert_eq!(sql("/* header; */\nSELECT 1;"), ["SELECT 1"]);
+        assert!(sql("/* only a comment; */").is_empty());
+    }
+
+    #[test]
+    fn block_comments_inside_a_statement_are_kept() {
+        assert_eq!(sql("SELECT /* a; b */ 1;\nSELECT 2;"), ["SELECT /* a; b */ 1", "SELECT 2"]);
+        assert_eq!(sql("SELECT 1 /* multi\nline; */ + 1;"), ["SELECT 1 /* multi\nline; */ + 1"]);
+    }
+
+    #[test]
+    fn keeps_conditional_comments() {
+        assert_eq!(
+            sql("/*!40101 SET NAMES utf8mb4 */;\n/*!40000 ALTER TABLE `t` DISABLE KEYS */;"),
+            ["/*!40101 SET NAMES utf8mb4 */", "/*!40000 ALTER TABLE `t` DISABLE KEYS */"]
+        );
+        assert_eq!(sql("/*M!100100 SET a = 1 */;"), ["/*M!100100 SET a = 1 */"]);
+        assert_eq!(sql("SELECT /*+ MAX_EXECUTION_TIME(1000) */ 1;"), ["SELECT /*+ MAX_EXECUTION_TIME(1000) */ 1"]);
+    }
+
+    #[test]
+    fn does_not_split_inside_conditional_comments() {
+        assert_eq!(sql("/*!50003 SET @a = 1; SET @b = '*/' */; SELECT 1;"), ["/*!50003 SET @a = 1; SET @b = '*/' */", "SELECT 1"]);
+    }
+
+    #[test]
+    fn honours_delimiter_command() {
+        let script = "DELIMITER $$\n\
+            CREATE PROCEDURE p()\n\
+            BEGIN\n  SELECT 1;\n  SELECT 2;\nEND$$\n\
+            DELIMITER ;\n\
+            CALL p();\n";
+        assert_eq!(
+            sql(script),
+            ["CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND", "CALL p()"]
+        );
+    }
+
+    #[test]
+    fn delimiter_command_is_case_insensitive_and_takes_any_token() {
+        assert_eq!(sql("delimiter //\nSELECT 1; SELECT 2//\nDeLiMiTeR ;\nSELECT 3;"), ["SELECT 1; SELECT 2", "SELECT 3"]);
+    }
+
+    #[test]
+    fn delimiter_command_only_counts_at_statement_start() {
+        assert_eq!(sql("SELECT delimiter FROM t;"), ["SELECT delimiter FROM t"]);
+        assert_eq!(sql("SELECT 1\nDELIMITER $$\n;"), ["SELECT 1\nDELIMITER $$"]);
+        assert_eq!(sql("SELECT `delimiter $$`;"), ["SELECT `delimiter $$`"]);
+    }
+
+    #[test]
+    fn delimiter_glued_to_a_word_still_ends_the_statement() {
+        assert_eq!(sql("DELIMITER $$\nSELECT a$$SELECT b$$"), ["SELECT a", "SELECT b"]);
+    }
+
+    #[test]
+    fn splits_mysqldump_triggers() {
+        let script = "DELIMITER ;;\n\
+            /*!50003 CREATE*/ /*!50017 DEFINER=`root`@`localhost`*/ /*!50003 TRIGGER `trg` BEFORE INSERT ON `t` FOR EACH ROW BEGIN\n\
+            \x20 SET NEW.a = 1;\n\
+            END */;;\n\
+            DELIMITER ;\n\
+            /*!50003 SET sql_mode = @saved_sql_mode */ ;\n";
+        let statements = sql(script);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].starts_with("/*!50003 CREATE*/"));
+        assert!(statements[0].ends_with("END */"));
+        assert_eq!(statements[1], "/*!50003 SET sql_mode = @saved_sql_mode */");
+    }
+
+    #[test]
+    fn keeps_routine_bodies_together_without_delimiter() {
+        let script = "CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND;\nSELECT 3;";
+        assert_eq!(sql(script), ["CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND", "SELECT 3"]);
+    }
+
+    #[test]
+    fn handles_nested_blocks_and_control_flow() {
+        let script = "CREATE DEFINER=`root`@`%` PROCEDURE p(x INT)\n\
+            outer_block: BEGIN\n\
+            \x20 IF x > 0 THEN\n\
+            \x20   BEGIN\n\
+            \x20     SELECT CASE WHEN x = 1 THEN 'one' ELSE 'many' END;\n\
+            \x20   END;\n\
+            \x20 ELSEIF x < 0 THEN\n\
+            \x20   CASE x WHEN -1 THEN SELECT 1; ELSE SELECT 2; END CASE;\n\
+            \x20 END IF;\n\
+            \x20 l: LOOP\n\
+            \x20   LEAVE l;\n\
+            \x20 END LOOP l;\n\
+            \x20 WHILE x > 0 DO SET x = x - 1; END WHILE;\n\
+            \x20 REPEAT SET x = x + 1; UNTIL x > 5 END REPEAT;\n\
+            END outer_block;\n\
+            SELECT 'after';";
+        let statements = sql(script);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("END outer_block"));
+        assert_eq!(statements[1], "SELECT 'after'");
+    }
+
+    #[test]
+    fn splits_each_compound_kind() {
+        let script = "CREATE FUNCTION f() RETURNS INT DETERMINISTIC BEGIN RETURN 1; END;\n\
+            CREATE TRIGGER t BEFORE INSERT ON x FOR EACH ROW BEGIN SET NEW.a = 1; END;\n\
+            CREATE EVENT e ON SCHEDULE EVERY 1 DAY DO BEGIN DELETE FROM x; END;\n\
+            ALTER EVENT e DO BEGIN DELETE FROM y; END;\n\
+            CREATE OR REPLACE PROCEDURE p() BEGIN END;";
+        assert_eq!(split_sql(script).len(), 5);
+    }
+
+    #[test]
+    fn routine_without_body_ends_at_first_delimiter() {
+        assert_eq!(
+            sql("CREATE FUNCTION f() RETURNS INT RETURN 1; SELECT f();"),
+            ["CREATE FUNCTION f() RETURNS INT RETURN 1", "SELECT f()"]
+        );
+        assert_eq!(
+            sql("CREATE TRIGGER t BEFORE INSERT ON x FOR EACH ROW SET NEW.a = CASE WHEN NEW.a IS NULL THEN 0 ELSE NEW.a END; SELECT 1;"),
+            [
+                "CREATE TRIGGER t BEFORE INSERT ON x FOR EACH ROW SET NEW.a = CASE WHEN NEW.a IS NULL THEN 0 ELSE NEW.a END",
+                "SELECT 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn begin_as_transaction_start_is_not_a_block() {
+        assert_eq!(
+            sql("BEGIN; INSERT INTO t VALUES (1); COMMIT;\nBEGIN WORK; ROLLBACK;"),
+            ["BEGIN", "INSERT INTO t VALUES (1)", "COMMIT", "BEGIN WORK", "ROLLBACK"]
+        );
+    }
+
+    #[test]
+    fn keeps_mariadb_anonymous_blocks_together() {
+        assert_eq!(
+            sql("BEGIN NOT ATOMIC SELECT 1; SELECT 2; END; SELECT 3;"),
+            ["BEGIN NOT ATOMIC SELECT 1; SELECT 2; END", "SELECT 3"]
+        );
+    }
+
+    #[test]
+    fn block_keywords_as_identifiers_do_not_nest() {
+        assert_eq!(
+            sql("CREATE TABLE event_log (begin DATETIME, end DATETIME, `case` INT); SELECT 1;"),
+            ["CREATE TABLE event_log (begin DATETIME, end DATETIME, `case` INT)", "SELECT 1"]
+        );
+        assert_eq!(
+            sql("CREATE PROCEDURE p() BEGIN SELECT t.begin, t.end FROM t; END; SELECT 1;"),
+            ["CREATE PROCEDURE p() BEGIN SELECT t.begin, t.end FROM t; END", "SELECT 1"]
+        );
+        assert_eq!(
+            sql("CREATE PROCEDURE p() BEGIN SELECT 'BEGIN', `case`; END; SELECT 1;"),
+            ["CREATE PROCEDURE p() BEGIN SELECT 'BEGIN', `case`; END", "SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn custom_delimiter_disables_block_tracking() {
+        // An unbalanced body must not swallow the rest of the script
+        assert_eq!(sql("DELIMITER $$\nCREATE PROCEDURE p() BEGIN SELECT 1$$\nSELECT 2$$"), [
+            "CREATE PROCEDURE p() BEGIN SELECT 1",
+            "SELECT 2"
+        ]);
+    }
+
+    #[test]
+    fn honours_go_terminators() {
+        assert_eq!(sql("SELECT 1\\G\nSELECT 2\\gSELECT 3;"), ["SELECT 1", "SELECT 2", "SELECT 3"]);
+        assert_eq!(sql("SELECT '\\G';"), ["SELECT '\\G'"]);
+    }
+
+    #[test]
+    fn reports_line_and_offset_spans() {
+        let script = "-- header\nSELECT 1;\n\nSELECT\n  2\n;  SELECT 'é'; ";
+        let statements = split_sql(script);
+        assert_eq!(statements.len(), 3);
+
+        assert_eq!((statements[0].start_line, statements[0].end_line), (2, 2));
+        assert_eq!(&script[statements[0].start_offset..statements[0].end_offset], "SELECT 1");
+
+        assert_eq!((statements[1].start_line, statements[1].end_line), (4, 5));
+        assert_eq!(&script[statements[1].start_offset..statements[1].end_offset], "SELECT\n  2");
+
+        assert_eq!((statements[2].start_line, statements[2].end_line), (6, 6));
+        assert_eq!(&script[statements[2].start_offset..statements[2].end_offset], "SELECT 'é'");
+    }
+
+    #[test]
+    fn spans_skip_leading_comments_and_delimiter_commands() {
+        let script = "DELIMITER $$\n/* doc */\nCREATE PROCEDURE p()\nBEGIN\nEND $$\n";
+        let statement = &split_sql(script)[0];
+        assert_eq!((statement.start_line, statement.end_line), (3, 5));
+        assert_eq!(&script[statement.start_offset..statement.end_offset], "CREATE PROCEDURE p()\nBEGIN\nEND");
+    }
+
+    #[test]
+    fn handles_crlf_line_endings() {
+        let statements = split_sql("SELECT 1; -- note\r\nSELECT\r\n2;\r\n");
+        assert_eq!(statements.iter().map(|s| s.sql.as_str()).collect::<Vec<_>>(), ["SELECT 1", "SELECT\r\n2"]);
+        assert_eq!((statements[1].start_line, statements[1].end_line), (2, 3));
+    }
+
+    #[test]
+    fn returns_unterminated_trailing_statement() {
+        assert_eq!(sql("SELECT 1;\nSELECT 'unterminated"), ["SELECT 1", "SELECT 'unterminated"]);
+    }
+
+    #[test]
+    fn chunked_feeding_matches_whole_input() {
+        let script = "DELIMITER ;;\n/*!50003 CREATE*/ /*!50003 TRIGGER trg BEFORE INSERT ON t FOR EACH ROW BEGIN SET NEW.a = 'x;y'; END */;;\n\
+            DELIMITER ;\n-- comment; here\nINSERT INTO t VALUES ('ü;', \"q\"\"\"), (2, 'back\\\\');\n\
+            CREATE PROCEDURE p() BEGIN IF 1 THEN SELECT 1; END IF; END;\nSELECT 1\\G SELECT 2";
+        let expected = split_sql(script);
+        assert_eq!(expected.len(), 5);
+
+        for size in 1..=7 {
+            let mut splitter = SqlSplitter::new();
+            let mut statements = Vec::new();
+            let chars: Vec<char> = script.chars().collect();
+            for chunk in chars.chunks(size) {
+                statements.extend(splitter.feed(&chunk.iter().collect::<String>()));
+            }
+            statements.extend(splitter.finish());
+            assert_eq!(statements, expected, "chunk size {}", size);
+        }
+    }
+
+    #[test]
+    fn tracks_delimiter_and_line() {
+        let mut splitter = SqlSplitter::new();
+        assert!(splitter.feed("DELIMITER $$\nSELECT 1").is_empty());
+        assert_eq!(splitter.delimiter(), "$$");
+        assert_eq!(splitter.line(), 2);
+        let statements = splitter.feed("$$\n");
+        assert_eq!(statements.len(), 1);
+        assert_eq!(statements[0].start_line, 2);
+        assert!(splitter.finish().is_empty());
+    }
+}
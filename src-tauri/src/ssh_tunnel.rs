@@ -0,0 +1,316 @@
+use ssh2::{CheckResult, KnownHostFileKind, KnownHostKeyFormat};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const KEEPALIVE_INTERVAL: u32 = 30;
+const IDLE_SLEEP: Duration = Duration::from_millis(2);
+/// libssh2's EAGAIN, returned by non-blocking calls that need another round
+const LIBSSH2_ERROR_EAGAIN: i32 = -37;
+
+pub struct SshTunnelConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub pass: Option<String>,
+    pub key_path: Option<String>,
+    pub key_passphrase: Option<String>,
+    pub known_hosts_path: Option<String>,
+    /// Refuse hosts missing from known_hosts instead of trusting them on first use. A key
+    /// trusted on first use is recorded in known_hosts, so a later change is refused.
+    pub strict_host_key: bool,
+}
+
+/// Local port forward `127.0.0.1:<local_port>` -> `<remote_host>:<remote_port>` through an SSH server.
+///
+/// The forwarding runs on its own thread and stops when the tunnel is closed or dropped.
+pub struct SshTunnel {
+    pub local_port: u16,
+    shutdown: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SshTunnel {
+    /// Blocking: connects, verifies and authenticates. Call it from `spawn_blocking`.
+    pub fn open(config: &SshTunnelConfig, remote_host: &str, remote_port: u16) -> Result<Self, String> {
+        let addr = (config.host.as_str(), config.port)
+            .to_socket_addrs()
+            .map_err(|e| format!("SSH: cannot resolve {}: {}", config.host, e))?
+            .next()
+            .ok_or_else(|| format!("SSH: cannot resolve {}", config.host))?;
+        let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+            .map_err(|e| format!("SSH: cannot reach {}:{}: {}", config.host, config.port, e))?;
+
+        let mut session = ssh2::Session::new().map_err(|e| format!("SSH: {}", e))?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(CONNECT_TIMEOUT.as_millis() as u32);
+        session.handshake().map_err(|e| format!("SSH handshake failed: {}", e))?;
+
+        verify_host_key(&session, config)?;
+        authenticate(&session, config)?;
+
+        let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let local_port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+        session.set_timeout(0);
+        session.set_keepalive(false, KEEPALIVE_INTERVAL);
+        session.set_blocking(false);
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let flag = shutdown.clone();
+        let remote_host = remote_host.to_string();
+        let worker = std::thread::Builder::new()
+            .name(format!("ssh-tunnel-{}", local_port))
+            .spawn(move || forward_loop(session, listener, remote_host, remote_port, flag))
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            local_port,
+            shutdown,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn close(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn default_known_hosts() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".ssh").join("known_hosts"))
+}
+
+fn verify_host_key(session: &ssh2::Session, config: &SshTunnelConfig) -> Result<(), String> {
+    let (key, key_type) = session.host_key().ok_or("SSH: server did not send a host key")?;
+
+    let mut known_hosts = session.known_hosts().map_err(|e| format!("SSH: {}", e))?;
+    let path = config.known_hosts_path.as_ref().map(PathBuf::from).or_else(default_known_hosts);
+    if let Some(path) = path.as_ref().filter(|p| p.exists()) {
+        known_hosts
+            .read_file(path, KnownHostFileKind::OpenSSH)
+            .map_err(|e| format!("SSH: cannot read {}: {}", path.display(), e))?;
+    }
+
+    match known_hosts.check_port(&config.host, config.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound if !config.strict_host_key => {
+            // Trust on first use: remember the key so a different one is refused next time
+            let path = path.ok_or("SSH: no known_hosts file to record the host key in")?;
+            let host = if config.port == 22 {
+                config.host.clone()
+            } else {
+                format!("[{}]:{}", config.host, config.port)
+            };
+            known_hosts
+                .add(&host, key, "", KnownHostKeyFormat::from(key_type))
+                .map_err(|e| format!("SSH: cannot record host key for {}: {}", config.host, e))?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| format!("SSH: cannot create {}: {}", dir.display(), e))?;
+            }
+            known_hosts
+                .write_file(&path, KnownHostFileKind::OpenSSH)
+                .map_err(|e| format!("SSH: cannot write {}: {}", path.display(), e))?;
+            log::info!("SSH: added host key for {} to {}", host, path.display());
+            Ok(())
+        }
+        CheckResult::NotFound => Err(format!("SSH: host key for {} is not in known_hosts", config.host)),
+        CheckResult::Mismatch => Err(format!(
+            "SSH: host key for {} does not match known_hosts. Possible man-in-the-middle attack!",
+            config.host
+        )),
+        CheckResult::Failure => Err("SSH: host key verification failed".to_string()),
+    }
+}
+
+fn authenticate(session: &ssh2::Session, config: &SshTunnelConfig) -> Result<(), String> {
+    if let Some(key_path) = config.key_path.as_ref().filter(|p| !p.is_empty()) {
+        let passphrase = config.key_passphrase.as_deref().filter(|p| !p.is_empty());
+        session
+            .userauth_pubkey_file(&config.user, None, std::path::Path::new(key_path), passphrase)
+            .map_err(|e| format!("SSH key authentication failed: {}", e))?;
+    } else if let Some(pass) = &config.pass {
+        session
+            .userauth_password(&config.user, pass)
+            .map_err(|e| format!("SSH password authentication failed: {}", e))?;
+    } else {
+        session
+            .userauth_agent(&config.user)
+            .map_err(|e| format!("SSH agent authentication failed: {}", e))?;
+    }
+
+    if !session.authenticated() {
+        return Err("SSH authentication failed".to_string());
+    }
+    Ok(())
+}
+
+/// One forwarded client connection and the bytes waiting to go each way.
+struct Forward {
+    local: TcpStream,
+    channel: ssh2::Channel,
+    to_remote: Vec<u8>,
+    to_local: Vec<u8>,
+    local_eof: bool,
+    closed: bool,
+}
+
+impl Forward {
+    /// Moves as much data as possible without blocking. Returns `true` if anything moved.
+    fn pump(&mut self, buf: &mut [u8]) -> bool {
+        let mut progressed = false;
+
+        if self.to_remote.is_empty() && !self.local_eof {
+            match self.local.read(buf) {
+                Ok(0) => self.local_eof = true,
+                Ok(n) => {
+                    self.to_remote.extend_from_slice(&buf[..n]);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.closed = true,
+            }
+        }
+
+        if !self.to_remote.is_empty() {
+            match self.channel.write(&self.to_remote) {
+                Ok(n) => {
+                    self.to_remote.drain(..n);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.closed = true,
+            }
+        }
+
+        if self.to_local.is_empty() {
+            match self.channel.read(buf) {
+                Ok(0) => {
+                    if self.channel.eof() {
+                        self.closed = true;
+                    }
+                }
+                Ok(n) => {
+                    self.to_local.extend_from_slice(&buf[..n]);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.closed = true,
+            }
+        }
+
+        if !self.to_local.is_empty() {
+            match self.local.write(&self.to_local) {
+                Ok(n) => {
+                    self.to_local.drain(..n);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.closed = true,
+            }
+        }
+
+        // The client hung up and everything it sent has been delivered
+        if self.local_eof && self.to_remote.is_empty() {
+            self.closed = true;
+        }
+
+        progressed
+    }
+}
+
+fn forward_loop(
+    session: ssh2::Session,
+    listener: TcpListener,
+    remote_host: String,
+    remote_port: u16,
+    shutdown: Arc<AtomicBool>,
+) {
+    let mut waiting: Vec<TcpStream> = Vec::new();
+    let mut forwards: Vec<Forward> = Vec::new();
+    let mut buf = vec![0u8; 32 * 1024];
+    let mut next_keepalive = Instant::now();
+
+    while !shutdown.load(Ordering::SeqCst) {
+        let mut progressed = false;
+
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if stream.set_nonblocking(true).is_ok() {
+                    let _ = stream.set_nodelay(true);
+                    waiting.push(stream);
+                }
+                progressed = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(_) => break,
+        }
+
+        // Open a channel for each accepted client; EAGAIN just means "try again next round"
+        let mut still_waiting = Vec::new();
+        for stream in waiting.drain(..) {
+            match session.channel_direct_tcpip(&remote_host, remote_port, None) {
+                Ok(channel) => {
+                    forwards.push(Forward {
+                        local: stream,
+                        channel,
+                        to_remote: Vec::new(),
+                        to_local: Vec::new(),
+                        local_eof: false,
+                        closed: false,
+                    });
+                    progressed = true;
+                }
+                Err(e) if e.code() == ssh2::ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => still_waiting.push(stream),
+                Err(e) => log::warn!("SSH tunnel: cannot open channel to {}:{}: {}", remote_host, remote_port, e),
+            }
+        }
+        waiting = still_waiting;
+
+        for fwd in forwards.iter_mut() {
+            progressed |= fwd.pump(&mut buf);
+        }
+        forwards.retain_mut(|fwd| {
+            if fwd.closed {
+                let _ = fwd.channel.close();
+                let _ = fwd.local.shutdown(std::net::Shutdown::Both);
+            }
+            !fwd.closed
+        });
+
+        if Instant::now() >= next_keepalive {
+            let secs = session.keepalive_send().unwrap_or(KEEPALIVE_INTERVAL);
+            next_keepalive = Instant::now() + Duration::from_secs(secs.max(1) as u64);
+        }
+
+        if !progressed {
+            std::thread::sleep(IDLE_SLEEP);
+        }
+    }
+
+    for mut fwd in forwards {
+        let _ = fwd.channel.close();
+        let _ = fwd.local.shutdown(std::net::Shutdown::Both);
+    }
+    let _ = session.disconnect(None, "Tunnel closed", None);
+}
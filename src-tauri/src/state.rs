@@ -1,38 +1,275 @@
-use mysql_async::Pool;
+use crate::query_history::QueryHistory;
+use crate::secret_store::SecretStore;
+use crate::ssh_tunnel::SshTunnel;
+use mysql_async::{Conn, Pool};
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Error returned by a command whose query was stopped with `cancel_query`.
+pub const QUERY_CANCELLED: &str = "Query cancelled";
 
 pub struct AppState {
-    pub pool: Mutex<Option<Pool>>,
+    /// Open connections keyed by session id (the saved server id when there is one)
+    pub sessions: Mutex<HashMap<String, Session>>,
+    /// Session used by commands that don't name one explicitly
+    pub active_session: Mutex<Option<String>>,
+    /// Credential vault, `None` while locked
+    pub secret_store: Mutex<Option<Box<dyn SecretStore>>>,
+    /// Queries that can be cancelled, keyed by the request token the frontend chose
+    pub running_queries: Mutex<HashMap<String, RunningQuery>>,
+    /// Loaded on first use, see `commands::history::with_history`
+    pub history: Mutex<Option<QueryHistory>>,
+}
+
+pub struct RunningQuery {
+    pub session_id: String,
+    /// Server thread id of the connection running the query (`CONNECTION_ID()`)
+    pub connection_id: u32,
+    pub cancelled: Arc<AtomicBool>,
+}
+
+/// Keeps a query registered for cancellation until dropped.
+pub struct QueryTracker<'a> {
+    state: &'a AppState,
+    token: Option<String>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl QueryTracker<'_> {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Turns a query error into `QUERY_CANCELLED` when the failure came from `cancel_query`.
+    pub fn error(&self, err: impl ToString) -> String {
+        if self.is_cancelled() {
+            QUERY_CANCELLED.to_string()
+        } else {
+            err.to_string()
+        }
+    }
+
+    /// Points the registration at a new connection, after the one the query ran on was replaced.
+    pub fn set_connection_id(&self, connection_id: u32) {
+        if let Some(token) = &self.token {
+            if let Some(query) = self.state.running_queries.lock().unwrap().get_mut(token) {
+                query.connection_id = connection_id;
+            }
+        }
+    }
+
+    /// Fails with `QUERY_CANCELLED` if a cancel arrived, even when the statement itself finished.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(QUERY_CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for QueryTracker<'_> {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            self.state.running_queries.lock().unwrap().remove(token);
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct ServerConfig {
+pub struct Session {
     pub id: String,
     pub name: String,
     pub host: String,
     pub port: u16,
     pub user: String,
-    pub pass: Option<String>,
-    pub ssl: Option<bool>,
-    pub ssh_enabled: Option<bool>,
-    pub ssh_host: Option<String>,
-    pub ssh_port: Option<u16>,
-    pub ssh_user: Option<String>,
-    pub ssh_pass: Option<String>,
-    pub auto_connect: Option<bool>,
+    pub connected_at: String,
+    pub pool: Pool,
+    /// SSH port forward the pool dials through, torn down with the session
+    pub tunnel: Option<SshTunnel>,
+    /// Open explicit transaction; while set, session queries run on its connection
+    pub transaction: Option<PinnedTransaction>,
+}
+
+/// A connection taken out of the pool for `begin_transaction` until commit or rollback.
+pub struct PinnedTransaction {
+    pub conn: Arc<tokio::sync::Mutex<Conn>>,
+    pub connection_id: u32,
+    pub started_at: String,
+    pub last_activity: Arc<Mutex<Instant>>,
+}
+
+/// Connection handed to a command: the session's pinned transaction connection if one is
+/// open, otherwise a fresh one from the pool.
+pub enum SessionConn {
+    Pooled(Conn),
+    Pinned {
+        conn: tokio::sync::OwnedMutexGuard<Conn>,
+        last_activity: Arc<Mutex<Instant>>,
+    },
+}
+
+impl SessionConn {
+    pub fn is_pinned(&self) -> bool {
+        matches!(self, SessionConn::Pinned { .. })
+    }
+}
+
+impl Deref for SessionConn {
+    type Target = Conn;
+
+    fn deref(&self) -> &Conn {
+        match self {
+            SessionConn::Pooled(conn) => conn,
+            SessionConn::Pinned { conn, .. } => conn,
+        }
+    }
+}
+
+impl DerefMut for SessionConn {
+    fn deref_mut(&mut self) -> &mut Conn {
+        match self {
+            SessionConn::Pooled(conn) => conn,
+            SessionConn::Pinned { conn, .. } => conn,
+        }
+    }
+}
+
+impl Drop for SessionConn {
+    fn drop(&mut self) {
+        // Idle time is measured from when the last command let go of the connection
+        if let SessionConn::Pinned { last_activity, .. } = self {
+            *last_activity.lock().unwrap() = Instant::now();
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct SessionInfo {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub connected_at: String,
+    pub active: bool,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            active_session: Mutex::new(None),
+            secret_store: Mutex::new(None),
+            running_queries: Mutex::new(HashMap::new()),
+            history: Mutex::new(None),
+        }
+    }
+
+    /// Resolves the session to use: the requested one, or the active one when `None`.
+    pub fn resolve_session_id(&self, session_id: Option<&str>) -> Result<String, String> {
+        match session_id {
+            Some(id) if !id.is_empty() => Ok(id.to_string()),
+            _ => self.active_session.lock().unwrap().clone().ok_or_else(|| "Not connected".to_string()),
+        }
+    }
+
+    /// Returns a handle to the pool of the given (or active) session.
+    pub fn get_pool(&self, session_id: Option<&str>) -> Result<Pool, String> {
+        let id = self.resolve_session_id(session_id)?;
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(&id)
+            .map(|s| s.pool.clone())
+            .ok_or_else(|| format!("Session '{}' is not connected", id))
+    }
+
+    /// Connection for a command that should see (and take part in) an open transaction.
+    /// Waits if another command is currently using the pinned connection.
+    pub async fn get_conn(&self, session_id: Option<&str>) -> Result<SessionConn, String> {
+        let id = self.resolve_session_id(session_id)?;
+        let (pool, pinned) = {
+            let sessions = self.sessions.lock().unwrap();
+            let session = sessions.get(&id).ok_or_else(|| format!("Session '{}' is not connected", id))?;
+            let pinned = session.transaction.as_ref().map(|t| (t.conn.clone(), t.last_activity.clone()));
+            (session.pool.clone(), pinned)
+        };
+
+        match pinned {
+            Some((conn, last_activity)) => Ok(SessionConn::Pinned {
+                conn: conn.lock_owned().await,
+                last_activity,
+            }),
+            None => Ok(SessionConn::Pooled(pool.get_conn().await.map_err(|e| e.to_string())?)),
+        }
+    }
+
+    /// Registers the query running on `connection_id` under `token` so `cancel_query` can stop it.
+    /// Without a token nothing is registered and the tracker never reports a cancel. A token
+    /// that is already in use is refused, so one request cannot unregister another.
+    pub fn track_query(&self, token: Option<String>, session_id: &str, connection_id: u32) -> Result<QueryTracker<'_>, String> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let token = token.filter(|t| !t.is_empty());
+        if let Some(token) = &token {
+            let mut running = self.running_queries.lock().unwrap();
+            if running.contains_key(token) {
+                return Err(format!("A query is already running under request id {}", token));
+            }
+            running.insert(
+                token.clone(),
+                RunningQuery {
+                    session_id: session_id.to_string(),
+                    connection_id,
+                    cancelled: cancelled.clone(),
+                },
+            );
+        }
+        Ok(QueryTracker {
+            state: self,
+            token,
+            cancelled,
+        })
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct DbConfig {
+    pub id: Option<String>,
+    pub name: Option<String>,
     pub host: String,
     pub user: String,
     pub pass: String,
     pub port: u16,
     pub ssl: Option<bool>,
+    pub ssl_mode: Option<String>, // "disabled" | "required" | "verify_ca" | "verify_identity"
+    pub ssl_ca_path: Option<String>,
+    pub ssl_cert_path: Option<String>,
+    pub ssl_key_path: Option<String>,
+    pub ssl_skip_verify: Option<bool>,
     pub ssh_enabled: Option<bool>,
     pub ssh_host: Option<String>,
     pub ssh_port: Option<u16>,
     pub ssh_user: Option<String>,
     pub ssh_pass: Option<String>,
+    pub ssh_key_path: Option<String>,
+    pub ssh_key_passphrase: Option<String>,
+    pub ssh_known_hosts_path: Option<String>,
+    pub ssh_strict_host_key: Option<bool>,
+    /// Default schema selected on connect
+    pub database: Option<String>,
+    /// Unix socket path, used instead of TCP for local servers
+    pub socket: Option<String>,
+    /// Seconds to wait for the first connection
+    pub connect_timeout: Option<u64>,
+    /// zlib level 0-9 for the client/server protocol, off when unset
+    pub compression_level: Option<u32>,
 }
@@ -19,6 +19,8 @@ import { dbApi } from './api/db';
 import { Settings } from './features/settings/Settings';
 import { NotFound } from './features/common/NotFound';
 import { NotificationContainer } from './components/ui/NotificationContainer';
+import { VaultUnlockModal } from './features/common/VaultUnlockModal';
+import { ensureVaultUnlocked } from './stores/useVaultStore';
 import { OmniBar } from './features/search/OmniBar';
 import { ServerLayout } from './features/server/ServerLayout';
 import { ServerDashboard } from './features/server/ServerDashboard';
@@ -57,7 +59,10 @@ function ServerContextLayout({ children }: { children?: React.ReactNode }) {
     // 1. Restore/Sync Server Context
     useEffect(() => {
         const initServer = async () => {
-             const servers = await dbApi.getSavedServers();
+             // Passwords are only available while the credential vault is unlocked; without
+             // them the connection is attempted with none
+             const unlocked = await ensureVaultUnlocked();
+             const servers = await dbApi.getSavedServers(unlocked);
              const target = servers.find((s: any) => s.id === serverId);
              if (target) {
                  try {
@@ -236,7 +241,6 @@ function PersistenceSync() {
         dashboardViewMode, 
         showSystemDbs, 
         tableViewMode, 
-        queryHistory,
         // Performance settings
         debugMode,
         performanceMonitoring,
@@ -264,7 +268,6 @@ function PersistenceSync() {
                         dashboardViewMode: prefs.dashboard_view_mode,
                         showSystemDbs: prefs.show_system_dbs,
                         tableViewMode: prefs.table_view_mode,
-                        queryHistory: prefs.query_history || [],
                         // Performance settings
                         debugMode: prefs.debug_mode || false,
                         performanceMonitoring: prefs.performance_monitoring || false,
@@ -296,10 +299,6 @@ function PersistenceSync() {
                     dashboard_view_mode: dashboardViewMode,
                     show_system_dbs: showSystemDbs,
                     table_view_mode: tableViewMode,
-                    query_history: queryHistory.map(q => ({
-                        ...q,
-                        timestamp: q.timestamp.toISOString() // Backend expects String ISO
-                    })),
                     // Performance settings
                     debug_mode: debugMode,
                     performance_monitoring: performanceMonitoring,
@@ -320,7 +319,7 @@ function PersistenceSync() {
         }, 500); // 500ms debounce
 
         return () => clearTimeout(timer);
-    }, [theme, accentColor, density, fontFamily, dashboardViewMode, showSystemDbs, tableViewMode, queryHistory, debugMode, performanceMonitoring, showPerformanceOverlay, logLevel, aiConfig, exportTemplates]);
+    }, [theme, accentColor, density, fontFamily, dashboardViewMode, showSystemDbs, tableViewMode, debugMode, performanceMonitoring, showPerformanceOverlay, logLevel, aiConfig, exportTemplates]);
 
     return null;
 }
@@ -331,6 +330,7 @@ function App() {
         <ThemeSync />
         <PersistenceSync />
         <NotificationContainer />
+        <VaultUnlockModal />
         <BrowserRouter
             future={{
                 v7_startTransition: true,
@@ -1,3 +1,4 @@
+import type { Channel } from '@tauri-apps/api/core';
 
 export interface SavedServer {
     id: string;
@@ -7,12 +8,38 @@ export interface SavedServer {
     user: string;
     pass?: string;
     ssl?: boolean;
+    ssl_mode?: 'disabled' | 'required' | 'verify_ca' | 'verify_identity';
+    ssl_ca_path?: string;
+    ssl_cert_path?: string;
+    ssl_key_path?: string;
+    ssl_skip_verify?: boolean;
     ssh_enabled?: boolean;
     ssh_host?: string;
     ssh_port?: number;
     ssh_user?: string;
     ssh_pass?: string;
+    ssh_key_path?: string;
+    ssh_key_passphrase?: string;
+    ssh_known_hosts_path?: string;
+    ssh_strict_host_key?: boolean;
+    database?: string;
+    socket?: string;
+    connect_timeout?: number;
+    compression_level?: number;
     auto_connect?: boolean;
+    folder?: string;
+    color?: string;
+    environment?: 'dev' | 'staging' | 'prod';
+}
+
+export interface SessionInfo {
+    id: string;
+    name: string;
+    host: string;
+    port: number;
+    user: string;
+    connected_at: string;
+    active: boolean;
 }
 
 export interface TriggerInfo {
@@ -78,6 +105,37 @@ export interface Filter {
     val: string;
 }
 
+/** Filter tree for `browse_table`; plain JSON, so saved filters can be stored as they are. */
+export type FilterNode =
+    | { type: 'group', combinator: 'and' | 'or', negated?: boolean, children: FilterNode[] }
+    | ({ type: 'condition' } & FilterCondition);
+
+export interface FilterCondition {
+    column: string;
+    /** Filter on an expression of the column instead of the column itself */
+    expr?: ColumnExpr | null;
+    op: FilterOp;
+    value?: any;
+    /** Operands of in/not_in, or the two bounds of between/not_between */
+    values?: any[];
+    like_mode?: 'pattern' | 'contains' | 'starts_with' | 'ends_with' | 'exact' | null;
+    date_range?: DateRange | null;
+}
+
+export type FilterOp =
+    | 'eq' | 'ne' | 'gt' | 'gte' | 'lt' | 'lte'
+    | 'like' | 'not_like' | 'in' | 'not_in' | 'between' | 'not_between'
+    | 'is_null' | 'is_not_null' | 'regexp' | 'not_regexp' | 'date_range';
+
+export type ColumnExpr =
+    | { kind: 'json_path', path: string }
+    | { kind: 'lower' | 'upper' | 'trim' | 'length' | 'date' | 'year' | 'month' };
+
+export type DateRange =
+    | { kind: 'today' | 'yesterday' | 'this_week' | 'this_month' | 'this_year' }
+    | { kind: 'last_days', days: number }
+    | { kind: 'between', from: string, to: string };
+
 export interface Database {
     name: string;
     size?: number; // Rust returns u64
@@ -105,11 +163,68 @@ export interface BrowseResult {
     count: number;
 }
 
+/** One rendered block per result set returned by `execute_query_html`. */
+export interface QueryResultHtml {
+    head_html: string;
+    body_html: string;
+    pagination_html: string;
+    count: number;
+    total_rows: number;
+    query_time: number;
+}
+
 export interface BrowseResultRaw {
     columns: string[];
     rows: any[][];
     total_rows: number;
     primary_key?: string;
+    row_identity: RowIdentity;
+}
+
+/** Columns used to address a row when editing; `full_row` means every column. */
+export interface RowIdentity {
+    kind: 'primary' | 'unique' | 'full_row';
+    columns: string[];
+}
+
+/** Column -> value map identifying one row, built from `RowIdentity.columns`. */
+export type RowKey = Record<string, any>;
+
+export interface EditResult {
+    matched: number;
+    affected: number;
+    /** Set when the original values no longer match the database */
+    conflict?: EditConflict | null;
+}
+
+export interface EditConflict {
+    /** The row as it is now; null if it was deleted */
+    current: Record<string, any> | null;
+}
+
+export type ChangeOperation =
+    | { type: 'insert', db: string, table: string, values: Record<string, any> }
+    | { type: 'update', db: string, table: string, key: RowKey, values: Record<string, any>, original?: Record<string, any> }
+    | { type: 'delete', db: string, table: string, key: RowKey };
+
+export interface OperationResult {
+    index: number;
+    status: 'planned' | 'applied' | 'failed' | 'rolled_back' | 'skipped';
+    /** Prepared statement text with `?` placeholders */
+    sql: string | null;
+    /** Parameter values as SQL literals */
+    params: string[];
+    affected_rows: number;
+    last_insert_id: number | null;
+    error: string | null;
+}
+
+export interface ChangesetResult {
+    dry_run: boolean;
+    applied: boolean;
+    operations: OperationResult[];
+    /** Undoing the failed batch went wrong; an open session transaction may be gone */
+    rollback_error: string | null;
 }
 
 export interface SearchResult {
@@ -141,7 +256,9 @@ export interface ColumnInfo {
 }
 
 export interface QueryOptions {
-    // Add fields if QueryOptions struct in Rust has them
+    rollback?: boolean;
+    disable_fk_checks?: boolean;
+    profile?: boolean; // Attach a QueryProfile to the last result set
 }
 
 export interface CsvPreview {
@@ -163,19 +280,79 @@ export interface ExportOptions {
     add_create_table: boolean;
     add_if_not_exists: boolean;
     data_insertion_mode: string; // "INSERT", "INSERT IGNORE", "REPLACE"
+    max_statement_bytes?: number; // Upper bound for one extended INSERT, default 1 MiB
+    rows_per_statement?: number;
+    compression?: 'none' | 'gzip' | 'zstd'; // Taken from the file extension when not set
+    // Written with the structure. Views and triggers default to on, routines and events to off.
+    export_views?: boolean;
+    export_triggers?: boolean;
+    export_routines?: boolean;
+    export_events?: boolean;
+    consistent_snapshot?: boolean; // One REPEATABLE READ snapshot, binlog position in the header
+    lock_all_tables?: boolean; // Global read lock for the whole dump, covers MyISAM; implies consistent_snapshot
+    binary_format?: 'hex' | 'binary'; // BLOB/BINARY values as 0x... (default) or _binary '...'
+}
+
+export interface ExportProgress {
+    table: string;
+    table_index: number;
+    table_count: number;
+    rows_exported: number;
+    rows_estimate: number | null;
+    bytes_written: number; // Uncompressed
+    table_done: boolean;
 }
 
+export interface ImportOptions {
+    on_error?: 'abort' | 'skip' | 'retry'; // 'retry' only retries deadlocks, lock wait timeouts and lost connections
+    retry_attempts?: number; // Attempts per statement under 'retry', default 3
+    retry_delay_ms?: number;
+}
+
+export interface ImportProgress {
+    bytes_read: number; // Compressed bytes for .gz/.zst files
+    total_bytes: number;
+    line: number;
+    statements_executed: number;
+    statements_failed: number;
+    current_table: string | null;
+}
+
+export interface ImportFailure {
+    start_line: number;
+    end_line: number;
+    statement: string;
+    error: string;
+    attempts: number;
+}
+
+export interface ImportReport {
+    statements_executed: number;
+    statements_failed: number;
+    bytes_read: number;
+    failures: ImportFailure[];
+    duration_ms: number;
+}
+
+/** Error message of a command stopped with cancel_query */
+export const QUERY_CANCELLED = 'Query cancelled';
+
 // Map of Command Name -> [ArgsType, ReturnType]
 export type TauriCommands = {
     // Server
-    'get_server_info': [undefined, string[]];
-    'get_saved_servers': [undefined, any[]]; // Returns generic server config json
-    'get_saved_servers_local': [undefined, SavedServer[]];
-    'save_server': [{ config: any }, void];
-    'save_server_local': [{ server: SavedServer }, SavedServer[]];
-    'delete_server': [{ id: string }, void];
-    'delete_server_local': [{ id: string }, SavedServer[]];
-    'connect_db': [{ config: any }, string];
+    'get_server_info': [undefined, ServerInfo];
+    'get_saved_servers': [{ reveal?: boolean } | undefined, SavedServer[]]; // Secrets only with reveal + unlocked vault
+    'save_server': [{ server: SavedServer }, SavedServer[]];
+    'delete_server': [{ id: string }, SavedServer[]];
+    'export_server_profiles': [{ path: string; ids?: string[] }, number]; // Returns the number exported
+    'import_server_profiles': [{ path: string }, SavedServer[]];
+    'get_vault_status': [undefined, VaultStatus];
+    'unlock_vault': [{ masterPassword: string }, void];
+    'lock_vault': [undefined, void];
+    'connect_db': [{ config: any }, string]; // Returns the session id
+    'list_sessions': [undefined, SessionInfo[]];
+    'switch_session': [{ sessionId: string }, void];
+    'disconnect_session': [{ sessionId?: string }, void];
     'get_process_list': [undefined, any[]];
     'get_status_variables': [{ filter?: string }, StatusVar[]]; 
     'get_server_variables': [{ filter?: string }, StatusVar[]]; 
@@ -197,16 +374,19 @@ export type TauriCommands = {
     // Table
     'get_tables': [{ db: string }, Table[]];
     'get_tables_html': [{ db: string, table?: string }, any]; // Returns TablesResultHtml
-    'browse_table': [{ db: string, table: string, page: number, limit: number, sort_column?: string, sort_direction?: string, filters?: Filter[] }, BrowseResultRaw];
+    'browse_table': [{ db: string, table: string, page: number, limit: number, sortColumn?: string, sortDirection?: string, filters?: Filter[], filter?: FilterNode, requestId?: string }, BrowseResultRaw];
     'browse_table_html': [{ db: string, table: string, page: number, limit: number }, BrowseResult];
-    'update_cell': [{ db: string, table: string, column: string, value: any, primary_key_col: string, primary_key_val: any }, void];
+    'update_cell': [{ db: string, table: string, column: string, value: any, key: RowKey, original?: Record<string, any> }, EditResult];
+    'update_row': [{ db: string, table: string, row: Record<string, any>, key: RowKey, original?: Record<string, any> }, EditResult];
+    'delete_rows': [{ db: string, table: string, keys: RowKey[] }, EditResult];
+    'apply_changeset': [{ operations: ChangeOperation[], dryRun?: boolean }, ChangesetResult];
     'get_columns': [{ db: string, table: string }, ColumnInfo[]]; 
     'get_table_count': [{ db: string, table: string }, number];
     'rename_table': [{ db: string, table: string, new_name: string, new_db?: string }, void];
     'truncate_table': [{ db: string, table: string }, void];
     'copy_table': [{ db: string, table: string, new_db: string, new_table: string, with_data: boolean }, void];
     'table_maintenance': [{ db: string, table: string, op: string }, string[][]];
-    'global_search': [{ db?: string, term: string }, SearchResult[]];
+    'global_search': [{ db?: string, term: string, requestId?: string }, SearchResult[]];
 
     // Snippets
     'get_snippets': [undefined, Snippet[]];
@@ -220,12 +400,25 @@ export type TauriCommands = {
     'explain_query': [{ sql: string }, string];
 
     // Query
-    'execute_query': [{ sql: string, db?: string, options?: QueryOptions }, QueryResult[]];
-    'execute_query_html': [{ sql: string, db?: string, options?: QueryOptions }, any];
+    'execute_query': [{ sql: string, db?: string, options?: QueryOptions, params?: QueryParams, requestId?: string }, QueryResult[]];
+    'execute_query_stream': [{ sql: string, db?: string, batchSize?: number, maxRows?: number, onEvent: Channel<QueryStreamEvent>, requestId?: string }, QueryStreamSummary];
+    'begin_transaction': [{ isolationLevel?: string, sessionId?: string }, void];
+    'commit_transaction': [{ sessionId?: string } | undefined, void];
+    'rollback_transaction': [{ sessionId?: string } | undefined, void];
+    'get_transaction_status': [{ sessionId?: string } | undefined, TransactionStatus];
+    'explain_query_plan': [{ sql: string, db?: string, analyze?: boolean }, QueryPlan];
+    'search_query_history': [{ filter?: HistoryFilter }, HistoryEntry[]];
+    'pin_query_history': [{ id: string, pinned: boolean }, void];
+    'delete_query_history': [{ id: string }, void];
+    'clear_query_history': [{ serverId?: string }, void];
+    'get_history_retention': [undefined, HistoryRetention];
+    'set_history_retention': [{ retention: HistoryRetention }, void];
+    'cancel_query': [{ requestId: string }, boolean]; // Cancelled command fails with QUERY_CANCELLED
+    'execute_query_html': [{ sql: string, db?: string }, QueryResultHtml[]];
 
     // Import/Export (Placeholder)
-    'export_database': [{ db: string, file: string, options: ExportOptions }, void];
-    'import_database': [{ db: string, file: string }, void];
+    'export_database': [{ db: string, filePath: string, options: ExportOptions, onProgress?: Channel<ExportProgress>, requestId?: string }, void];
+    'import_database': [{ db: string, filePath: string, options?: ImportOptions, onProgress?: Channel<ImportProgress>, requestId?: string }, ImportReport];
     'import_sql': [{ db: string, sql: string }, void];
     'get_csv_preview': [{ filePath: string, delimiter: string }, CsvPreview];
     'import_csv': [{ db: string, table: string, filePath: string, options: CsvImportOptions }, number];
@@ -328,7 +521,144 @@ export interface QueryResult {
     rows: any[][];
     affected_rows: number;
     last_insert_id: number;
+    warnings: number;
     duration_ms: number;
+    profile?: QueryProfile;
+}
+
+export interface QueryProfile {
+    source: 'performance_schema' | 'session_status';
+    server_time_ms?: number;
+    lock_time_ms?: number;
+    rows_examined?: number;
+    rows_sent?: number;
+    created_tmp_tables?: number;
+    created_tmp_disk_tables?: number;
+    sort_merge_passes?: number;
+    select_full_join?: number;
+    select_scan?: number;
+    no_index_used?: number;
+    stages: { stage: string; duration_ms: number }[];
+    truncated: boolean; // Statement counters only cover the last statements performance_schema kept
+    status_deltas: Record<string, number>;
+}
+
+/** Typed value for a `?` or `:name` placeholder. Binary values are base64. */
+export type QueryParam =
+    | { type: 'null' }
+    | { type: 'bool'; value: boolean }
+    | { type: 'int' | 'uint' | 'float'; value: number }
+    | { type: 'decimal' | 'string' | 'binary' | 'date_time'; value: string }
+    | { type: 'json'; value: any };
+
+/** Array for positional `?` placeholders, object for `:name` placeholders */
+export type QueryParams = QueryParam[] | Record<string, QueryParam>;
+
+export type QueryStreamEvent =
+    | { event: 'result_set'; index: number; columns: string[] }
+    | { event: 'rows'; index: number; rows: any[][]; fetched: number }
+    | { event: 'result_set_done'; index: number; affected_rows: number; last_insert_id: number; warnings: number; duration_ms: number; truncated: boolean };
+
+export interface QueryStreamSummary {
+    result_sets: number;
+    total_fetched: number;
+    truncated: boolean; // Row cap reached; the query was stopped there, so later statements did not run
+    duration_ms: number;
+}
+
+export interface PlanNode {
+    operation: string;
+    table?: string;
+    access_type?: string;
+    possible_keys: string[];
+    key?: string;
+    used_key_parts: string[];
+    rows_examined?: number;
+    rows_produced?: number;
+    filtered?: number;
+    cost?: number;
+    condition?: string;
+    using_index: boolean;
+    using_temporary: boolean;
+    using_filesort: boolean;
+    actual_rows?: number;
+    actual_loops?: number;
+    actual_time_ms?: number;
+    warnings: string[];
+    children: PlanNode[];
+}
+
+export interface AnalyzeNode {
+    operation: string;
+    estimated_cost?: number;
+    estimated_rows?: number;
+    actual_first_row_ms?: number;
+    actual_last_row_ms?: number;
+    actual_rows?: number;
+    loops?: number;
+    children: AnalyzeNode[];
+}
+
+export interface QueryPlan {
+    server: 'mysql' | 'mariadb';
+    analyzed: boolean;
+    total_cost?: number;
+    root: PlanNode;
+    analyze_tree?: AnalyzeNode; // MySQL EXPLAIN ANALYZE only
+    warnings: string[];
+    raw_json: any;
+}
+
+export interface HistoryEntry {
+    id: string;
+    sql: string;
+    timestamp: string;
+    server_id?: string;
+    database?: string;
+    duration_ms: number;
+    rows_affected: number;
+    rows_returned: number;
+    success: boolean;
+    error?: string;
+    pinned: boolean;
+}
+
+export interface HistoryFilter {
+    text?: string; // All terms must match the SQL, database or error
+    server_id?: string;
+    database?: string;
+    pinned_only?: boolean;
+    success?: boolean;
+    limit?: number;
+    offset?: number;
+}
+
+export interface HistoryRetention {
+    max_entries: number;
+    max_age_days?: number;
+}
+
+export interface TransactionStatus {
+    active: boolean;
+    started_at?: string;
+    idle_secs: number;
+    rows_locked: number;
+    tables_locked: number;
+    warnings: string[]; // Idle / lock warnings to surface to the user
+}
+
+export interface VaultStatus {
+    initialized: boolean;
+    unlocked: boolean;
+}
+
+export interface ServerInfo {
+    version: string;
+    uptime: string;
+    user: string;
+    ssl: boolean;
+    ssl_cipher?: string;
+    ssl_version?: string;
 }
 
 export interface ServerStatus {
@@ -1,5 +1,5 @@
-import { invoke } from '@tauri-apps/api/core';
-import { TauriCommands, CommandName, QueryResult } from './commands';
+import { invoke, Channel } from '@tauri-apps/api/core';
+import { TauriCommands, CommandName, QueryResult, QueryStreamEvent } from './commands';
 
 // Re-export types for consumers
 export type { Database, Table, BrowseResult, SavedServer } from './commands';
@@ -19,7 +19,8 @@ async function safeInvoke<K extends CommandName>(
     // Return mocks for development
     if (cmd === 'get_tables') return [] as any;
     if (cmd === 'get_databases') return [] as any;
-    if (cmd === 'get_saved_servers_local') return [] as any;
+    if (cmd === 'get_saved_servers') return [] as any;
+    if (cmd === 'search_query_history') return [] as any;
     return {} as any;
 }
 
@@ -58,24 +59,65 @@ export const dbApi = {
         return safeInvoke('browse_table_html', { db, table, page, limit });
     },
 
-    browseTableRaw: async (db: string, table: string, page: number, limit: number, sortColumn?: string, sortDirection?: string, filters?: import('./commands').Filter[]) => {
-        return safeInvoke('browse_table', { db, table, page, limit, sort_column: sortColumn, sort_direction: sortDirection, filters });
+    browseTableRaw: async (db: string, table: string, page: number, limit: number, sortColumn?: string, sortDirection?: string, filters?: import('./commands').Filter[], requestId?: string, filter?: import('./commands').FilterNode) => {
+        return safeInvoke('browse_table', { db, table, page, limit, sortColumn, sortDirection, filters, filter, requestId });
     },
 
-    updateCell: async (db: string, table: string, column: string, value: any, pk_col: string, pk_val: any) => {
-        return safeInvoke('update_cell', { db, table, column, value, primary_key_col: pk_col, primary_key_val: pk_val });
+    // `original` holds the values the row was loaded with; if the row has changed since, nothing
+    // is written and the result carries a conflict instead
+    updateCell: async (db: string, table: string, column: string, value: any, key: import('./commands').RowKey, original?: Record<string, any>) => {
+        return safeInvoke('update_cell', { db, table, column, value, key, original });
     },
 
-    updateRow: async (db: string, table: string, row: Record<string, any>, pk_col: string, pk_val: any) => {
-        return safeInvoke('update_row', { db, table, row, primary_key_col: pk_col, primary_key_val: pk_val });
+    updateRow: async (db: string, table: string, row: Record<string, any>, key: import('./commands').RowKey, original?: Record<string, any>) => {
+        return safeInvoke('update_row', { db, table, row, key, original });
+    },
+
+    deleteRows: async (db: string, table: string, keys: import('./commands').RowKey[]) => {
+        return safeInvoke('delete_rows', { db, table, keys });
+    },
+
+    // All-or-nothing; with dryRun the result only lists the statements that would run
+    applyChangeset: async (operations: import('./commands').ChangeOperation[], dryRun?: boolean) => {
+        return safeInvoke('apply_changeset', { operations, dryRun });
     },
 
     insertRows: async (db: string, table: string, rows: Record<string, any>[]) => {
         return safeInvoke('insert_rows', { db, table, rows });
     },
 
-    executeQuery: async (db: string, query: string, options?: any) => {
-        return safeInvoke('execute_query', { db, sql: query, options });
+    executeQuery: async (db: string, query: string, options?: any, requestId?: string, params?: import('./commands').QueryParams) => {
+        return safeInvoke('execute_query', { db, sql: query, options, params, requestId });
+    },
+
+    executeQueryStream: async (db: string, query: string, onEvent: (event: QueryStreamEvent) => void, opts?: { batchSize?: number, maxRows?: number, requestId?: string }) => {
+        const channel = new Channel<QueryStreamEvent>();
+        channel.onmessage = onEvent;
+        return safeInvoke('execute_query_stream', { db, sql: query, onEvent: channel, ...opts });
+    },
+
+    beginTransaction: async (isolationLevel?: string) => {
+        return safeInvoke('begin_transaction', { isolationLevel });
+    },
+
+    commitTransaction: async () => {
+        return safeInvoke('commit_transaction');
+    },
+
+    rollbackTransaction: async () => {
+        return safeInvoke('rollback_transaction');
+    },
+
+    getTransactionStatus: async () => {
+        return safeInvoke('get_transaction_status');
+    },
+
+    explainQueryPlan: async (db: string, sql: string, analyze?: boolean) => {
+        return safeInvoke('explain_query_plan', { db, sql, analyze });
+    },
+
+    cancelQuery: async (requestId: string) => {
+        return safeInvoke('cancel_query', { requestId });
     },
 
     getProcessList: async () => {
@@ -147,12 +189,44 @@ export const dbApi = {
         return safeInvoke('connect_db', { config });
     },
 
-    getSavedServers: async () => {
-        return safeInvoke('get_saved_servers_local');
+    listSessions: async () => {
+        return safeInvoke('list_sessions');
+    },
+
+    switchSession: async (sessionId: string) => {
+        return safeInvoke('switch_session', { sessionId });
+    },
+
+    disconnectSession: async (sessionId?: string) => {
+        return safeInvoke('disconnect_session', { sessionId });
+    },
+
+    getSavedServers: async (reveal?: boolean) => {
+        return safeInvoke('get_saved_servers', { reveal });
+    },
+
+    getVaultStatus: async () => {
+        return safeInvoke('get_vault_status');
+    },
+
+    unlockVault: async (masterPassword: string) => {
+        return safeInvoke('unlock_vault', { masterPassword });
+    },
+
+    lockVault: async () => {
+        return safeInvoke('lock_vault');
     },
 
     saveServer: async (server: any) => {
-        return safeInvoke('save_server_local', { server });
+        return safeInvoke('save_server', { server });
+    },
+
+    exportServerProfiles: async (path: string, ids?: string[]) => {
+        return safeInvoke('export_server_profiles', { path, ids });
+    },
+
+    importServerProfiles: async (path: string) => {
+        return safeInvoke('import_server_profiles', { path });
     },
 
     async getForeignKeys(db: string, table: string) {
@@ -160,7 +234,7 @@ export const dbApi = {
     },
 
     async deleteServer(id: string) {
-        return safeInvoke('delete_server_local', { id });
+        return safeInvoke('delete_server', { id });
     },
 
     dropDatabase: async (name: string) => {
@@ -213,12 +287,22 @@ export const dbApi = {
     },
 
     // Import/Export
-    exportDatabase: async (db: string, file: string, options: import('./commands').ExportOptions) => {
-        return safeInvoke('export_database', { db, file, options });
+    exportDatabase: async (db: string, filePath: string, options: import('./commands').ExportOptions, onProgress?: (progress: import('./commands').ExportProgress) => void, requestId?: string) => {
+        let channel: Channel<import('./commands').ExportProgress> | undefined;
+        if (onProgress) {
+            channel = new Channel();
+            channel.onmessage = onProgress;
+        }
+        return safeInvoke('export_database', { db, filePath, options, onProgress: channel, requestId });
     },
 
-    importDatabase: async (db: string, file: string) => {
-        return safeInvoke('import_database', { db, file });
+    importDatabase: async (db: string, filePath: string, options?: import('./commands').ImportOptions, onProgress?: (progress: import('./commands').ImportProgress) => void, requestId?: string) => {
+        let channel: Channel<import('./commands').ImportProgress> | undefined;
+        if (onProgress) {
+            channel = new Channel();
+            channel.onmessage = onProgress;
+        }
+        return safeInvoke('import_database', { db, filePath, options, onProgress: channel, requestId });
     },
 
     importSql: async (db: string, sql: string) => {
@@ -290,8 +374,25 @@ export const dbApi = {
     },
 
     // Search
-    globalSearch: async (term: string, db?: string) => {
-        return safeInvoke('global_search', { term, db });
+    globalSearch: async (term: string, db?: string, requestId?: string) => {
+        return safeInvoke('global_search', { term, db, requestId });
+    },
+
+    // Query History
+    searchQueryHistory: async (filter?: import('./commands').HistoryFilter) => {
+        return safeInvoke('search_query_history', { filter });
+    },
+
+    pinQueryHistory: async (id: string, pinned: boolean) => {
+        return safeInvoke('pin_query_history', { id, pinned });
+    },
+
+    deleteQueryHistory: async (id: string) => {
+        return safeInvoke('delete_query_history', { id });
+    },
+
+    clearQueryHistory: async (serverId?: string) => {
+        return safeInvoke('clear_query_history', { serverId });
     },
 
     // Snippets
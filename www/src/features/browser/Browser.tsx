@@ -3,10 +3,9 @@ import { useQuery, useMutation, keepPreviousData } from '@tanstack/react-query';
 import { useAppStore } from '@/stores/useAppStore';
 import { dbApi } from '@/api/db';
 import { Loader2, Table2, Edit2, Check, X, Key, Trash2, ArrowUp, ArrowDown, ArrowUpDown, Filter as FilterIcon, PlusCircle, AlertTriangle, Copy, Layers, GripVertical } from 'lucide-react';
-import type { Filter } from '@/api/commands';
+import type { Filter, RowKey } from '@/api/commands';
 import { cn } from '@/lib/utils';
 import { useVirtualizer } from '@tanstack/react-virtual';
-import { invoke } from '@tauri-apps/api/core';
 import { showToast } from '@/utils/ui';
 
 import { Modal } from '@/components/ui/Modal';
@@ -205,8 +204,8 @@ export function Browser() {
     const [limit, setLimit] = React.useState(25);
     const limitOptions = [10, 15, 25, 50, 100, 200, 300, 500, 700, 1000];
     
-    // Selection state
-    const [selectedRows, setSelectedRows] = React.useState<string[]>([]);
+    // Selection state (indexes into the current page's rows)
+    const [selectedRows, setSelectedRows] = React.useState<number[]>([]);
 
     // Filter state
     const [filters, setFilters] = React.useState<Filter[]>([]);
@@ -248,8 +247,7 @@ export function Browser() {
         isOpen: boolean;
         mode: 'edit' | 'insert';
         initialData?: Record<string, any>;
-        pkCol?: string;
-        pkVal?: any;
+        rowKey?: RowKey;
         columns?: string[];
     }>({
         isOpen: false,
@@ -279,6 +277,27 @@ export function Browser() {
         placeholderData: keepPreviousData
     });
 
+    // Selection is by position, so it does not survive a different page of rows
+    React.useEffect(() => {
+        setSelectedRows([]);
+    }, [page, limit, sortColumn, sortDirection, filters]);
+
+    const keyColumns = browseData && browseData.row_identity.kind !== 'full_row' ? browseData.row_identity.columns : [];
+
+    const rowKey = (row: any[]): RowKey => {
+        const key: RowKey = {};
+        if (!browseData) return key;
+        const { columns, row_identity } = browseData;
+        row_identity.columns.forEach(c => key[c] = row[columns.indexOf(c)]);
+        return key;
+    };
+
+    const rowObject = (row: any[]): Record<string, any> => {
+        const data: Record<string, any> = {};
+        browseData?.columns.forEach((c, i) => data[c] = row[i]);
+        return data;
+    };
+
     // Init visible columns and smart widths on data load
     React.useEffect(() => {
         if (browseData?.columns) {
@@ -336,46 +355,54 @@ export function Browser() {
 
     // 3. Update Cell Mutation
     const updateMutation = useMutation({
-        mutationFn: (args: { column: string, value: any, pkVal: any }) => 
-            dbApi.updateCell(currentDb!, currentTable!, args.column, args.value, browseData?.primary_key!, args.pkVal),
-        onSuccess: () => {
+        mutationFn: (args: { column: string, value: any, key: RowKey, original?: Record<string, any> }) => 
+            dbApi.updateCell(currentDb!, currentTable!, args.column, args.value, args.key, args.original),
+        onSuccess: (result, args) => {
+            if (result.conflict?.current) {
+                // Someone else changed the row since it was loaded; let the user decide
+                const current = result.conflict.current[args.column];
+                setConfirmState({
+                    isOpen: true,
+                    title: 'Edit Conflict',
+                    message: `This row was changed by someone else since it was loaded ("${args.column}" is now ${current === null ? 'NULL' : `"${current}"`}). Overwrite it with your value?`,
+                    onConfirm: () => {
+                        updateMutation.mutate({ column: args.column, value: args.value, key: args.key });
+                        setConfirmState(prev => ({ ...prev, isOpen: false }));
+                    }
+                });
+            } else if (result.matched === 0) {
+                showToast('Row no longer exists; nothing was updated', 'error');
+            }
             refetch();
         }
     });
 
     // 4. Delete Rows Mutation
     const deleteRowsMutation = useMutation({
-        mutationFn: async (ids: string[]) => {
-            if (!browseData?.primary_key) throw new Error("No primary key found");
-            return invoke('delete_rows', { 
-                db: currentDb, 
-                table: currentTable, 
-                primary_key: browseData.primary_key, 
-                ids 
-            });
+        mutationFn: async (indexes: number[]) => {
+            if (!browseData) throw new Error("No rows loaded");
+            return dbApi.deleteRows(currentDb!, currentTable!, indexes.map(i => rowKey(browseData.rows[i])));
         },
-        onSuccess: () => {
+        onSuccess: (result, indexes) => {
+            if (result.matched < indexes.length) {
+                showToast(`Deleted ${result.matched} of ${indexes.length} rows; the rest no longer exist`, 'error');
+            }
             setSelectedRows([]);
             refetch();
         }
     });
 
     const handleSelectAll = (checked: boolean) => {
-        if (checked && browseData?.rows && browseData.primary_key) {
-             // Find PK index
-             const pkIndex = browseData.columns.indexOf(browseData.primary_key);
-             if (pkIndex === -1) return;
-             
-             const allIds = browseData.rows.map(r => String(r[pkIndex]));
-             setSelectedRows(allIds);
+        if (checked && browseData?.rows) {
+             setSelectedRows(browseData.rows.map((_, i) => i));
         } else {
             setSelectedRows([]);
         }
     };
 
-    const handleSelectRow = (id: string, checked: boolean) => {
+    const handleSelectRow = (index: number, checked: boolean) => {
         setSelectedRows(prev => 
-            checked ? [...prev, id] : prev.filter(x => x !== id)
+            checked ? [...prev, index] : prev.filter(x => x !== index)
         );
     };
 
@@ -415,7 +442,6 @@ export function Browser() {
                             isOpen: true,
                             mode: 'insert',
                             initialData: undefined,
-                            pkCol: browseData?.primary_key,
                             columns: browseData?.columns || []
                         })}
                         className="flex items-center gap-1.5 px-3 py-1.5 text-xs font-bold rounded-md border text-text-muted border-transparent hover:bg-white/5 transition-colors"
@@ -665,7 +691,7 @@ export function Browser() {
                                      
                                      {/* ACTUAL HEADER - Rendered once, sticky */}
                                      <div className="sticky top-0 z-20 flex bg-surface/95 backdrop-blur border-b border-border shadow-sm min-w-max">
-                                         {browseData?.primary_key && (
+                                         {browseData && (
                                              <>
                                                 {/* Checkbox Header */}
                                                 <div className="sticky left-0 z-30 flex-none w-10 p-3 flex items-center justify-center border-r border-border/50 bg-surface/95 backdrop-blur">
@@ -690,7 +716,7 @@ export function Browser() {
                                                     key={col} 
                                                     className={cn(
                                                      "flex-none p-3 text-[11px] font-bold uppercase tracking-wider text-text-muted border-r border-border/50 truncate flex items-center gap-2 relative group select-none transition-colors",
-                                                     col === browseData.primary_key && "text-primary",
+                                                     keyColumns.includes(col) && "text-primary",
                                                      sortColumn === col ? "bg-white/5 text-primary" : "hover:bg-white/5"
                                                  )}
                                                  style={{ width: `${width}px` }}
@@ -716,7 +742,7 @@ export function Browser() {
                                                          <Copy size={10} />
                                                      </div>
 
-                                                     {col === browseData.primary_key && <Key size={10} className="text-yellow-500 shrink-0" />}
+                                                     {keyColumns.includes(col) && <Key size={10} className="text-yellow-500 shrink-0" />}
                                                      
                                                      {/* Resizer */}
                                                      <div 
@@ -746,10 +772,7 @@ export function Browser() {
                                         const row = browseData?.rows[virtualRow.index];
                                         if (!row) return null;
 
-                                        const pkIndex = browseData!.primary_key ? browseData!.columns.indexOf(browseData!.primary_key) : -1;
-                                        const pkVal = pkIndex >= 0 ? row[pkIndex] : null; 
-                                        const rowId = pkVal ? String(pkVal) : null;
-                                        const isSelected = rowId ? selectedRows.includes(rowId) : false;
+                                        const isSelected = selectedRows.includes(virtualRow.index);
 
                                         return (
                                             <div
@@ -765,7 +788,7 @@ export function Browser() {
                                                 }}
                                             >
                                                 {/* Selection Checkbox (Sticky Left: 0) */}
-                                                {browseData?.primary_key && (
+                                                {browseData && (
                                                     <div className="sticky left-0 z-10 flex-none w-10 flex items-center justify-center border-r border-border/50">
                                                         <div className="absolute inset-0 bg-canvas" />
                                                         <div className={cn("absolute inset-0 transition-colors", isSelected ? "bg-primary/10" : "group-hover:bg-white/5")} />
@@ -774,15 +797,14 @@ export function Browser() {
                                                                 type="checkbox" 
                                                                 className="cursor-pointer"
                                                                 checked={isSelected}
-                                                                onChange={e => rowId && handleSelectRow(rowId, e.target.checked)}
-                                                                disabled={!rowId}
+                                                                onChange={e => handleSelectRow(virtualRow.index, e.target.checked)}
                                                             />
                                                         </div>
                                                     </div>
                                                 )}
 
                                                 {/* Row Actions (Sticky Left: 10 via w-10) */}
-                                                {browseData?.primary_key && (
+                                                {browseData && (
                                                     <div className="sticky left-10 z-10 flex-none w-24 flex items-center justify-center gap-1 border-r border-border/50">
                                                         <div className="absolute inset-0 bg-canvas" />
                                                         <div className={cn("absolute inset-0 transition-colors", isSelected ? "bg-primary/10" : "group-hover:bg-white/5")} />
@@ -792,15 +814,13 @@ export function Browser() {
                                                                 className="p-1 hover:text-blue-400 transition-colors"
                                                                 onClick={() => {
                                                                     // Construct row object
-                                                                    const rowData: Record<string, any> = {};
-                                                                    browseData?.columns.forEach((c, i) => rowData[c] = row[i]);
+                                                                    const rowData = rowObject(row);
                                                                     
                                                                     setRowEditorState({
                                                                         isOpen: true,
                                                                         mode: 'edit',
                                                                         initialData: rowData,
-                                                                        pkCol: browseData?.primary_key,
-                                                                        pkVal: pkVal
+                                                                        rowKey: rowKey(row)
                                                                     });
                                                                 }}
                                                             >
@@ -810,35 +830,29 @@ export function Browser() {
                                                                 title="Insert as new row" 
                                                                 className="p-1 hover:text-green-400 transition-colors"
                                                                 onClick={() => {
-                                                                    if (rowId) {
-                                                                        const rowData: Record<string, any> = {};
-                                                                        browseData?.columns.forEach((c, i) => rowData[c] = row[i]);
-                                                                        
-                                                                        setRowEditorState({
-                                                                            isOpen: true,
-                                                                            mode: 'insert',
-                                                                            initialData: rowData,
-                                                                            pkCol: browseData?.primary_key,
-                                                                            columns: browseData?.columns || []
-                                                                        });
-                                                                    }
+                                                                    const rowData = rowObject(row);
+                                                                    
+                                                                    setRowEditorState({
+                                                                        isOpen: true,
+                                                                        mode: 'insert',
+                                                                        initialData: rowData,
+                                                                        columns: browseData?.columns || []
+                                                                    });
                                                                 }}
                                                             >
                                                                 <Copy size={12} />
                                                             </button>
                                                             <button 
                                                                 onClick={() => {
-                                                                    if (rowId) {
-                                                                        setConfirmState({
-                                                                            isOpen: true,
-                                                                            title: 'Delete Row',
-                                                                            message: 'Are you sure you want to permanently delete this row?',
-                                                                            onConfirm: () => {
-                                                                                deleteRowsMutation.mutate([rowId]);
-                                                                                setConfirmState(prev => ({ ...prev, isOpen: false }));
-                                                                            }
-                                                                        });
-                                                                    }
+                                                                    setConfirmState({
+                                                                        isOpen: true,
+                                                                        title: 'Delete Row',
+                                                                        message: 'Are you sure you want to permanently delete this row?',
+                                                                        onConfirm: () => {
+                                                                            deleteRowsMutation.mutate([virtualRow.index]);
+                                                                            setConfirmState(prev => ({ ...prev, isOpen: false }));
+                                                                        }
+                                                                    });
                                                                 }} 
                                                                 title="Delete" 
                                                                 className="p-1 hover:text-red-400 transition-colors"
@@ -866,13 +880,12 @@ export function Browser() {
                                                             <EditableCell 
                                                                 value={cell} 
                                                                 onSave={(val) => {
-                                                                    if (pkVal !== null) {
-                                                                        updateMutation.mutate({ 
-                                                                            column: col, 
-                                                                            value: val,
-                                                                            pkVal: pkVal
-                                                                        });
-                                                                    }
+                                                                    updateMutation.mutate({ 
+                                                                        column: col, 
+                                                                        value: val,
+                                                                        key: rowKey(row),
+                                                                        original: rowObject(row)
+                                                                    });
                                                                 }} 
                                                             />
                                                         </div>
@@ -954,8 +967,8 @@ export function Browser() {
                     onClose={() => setRowEditorState(prev => ({ ...prev, isOpen: false }))}
                     mode={rowEditorState.mode}
                     initialData={rowEditorState.initialData}
-                    pkCol={rowEditorState.pkCol}
-                    pkVal={rowEditorState.pkVal}
+                    rowKey={rowEditorState.rowKey}
+                    keyColumns={keyColumns}
                     columns={browseData.columns}
                     onSuccess={() => refetch()}
                 />
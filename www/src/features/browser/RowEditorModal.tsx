@@ -4,34 +4,46 @@ import { Loader2 } from 'lucide-react';
 import { dbApi } from '@/api/db';
 import { useAppStore } from '@/stores/useAppStore';
 import { showToast } from '@/utils/ui';
+import type { RowKey } from '@/api/commands';
 
 interface RowEditorModalProps {
     isOpen: boolean;
     onClose: () => void;
     mode: 'edit' | 'insert';
     initialData?: Record<string, any>; // For edit or copy
-    pkCol?: string; // Needed for edit to identify row
-    pkVal?: any; // Needed for edit
+    rowKey?: RowKey; // Needed for edit to identify row
+    keyColumns?: string[]; // Highlighted as key columns
     columns: string[]; // List of all columns
     onSuccess: () => void;
 }
 
-export function RowEditorModal({ isOpen, onClose, mode, initialData, pkCol, pkVal, columns, onSuccess }: RowEditorModalProps) {
+export function RowEditorModal({ isOpen, onClose, mode, initialData, rowKey, keyColumns = [], columns, onSuccess }: RowEditorModalProps) {
     const { currentDb, currentTable } = useAppStore();
     const [formData, setFormData] = useState<Record<string, any>>({});
+    // Values the row had when it was loaded, used to detect concurrent changes
+    const [original, setOriginal] = useState<Record<string, any> | undefined>(undefined);
     const [loading, setLoading] = useState(false);
+    // Asks whether to overwrite a row someone else changed or to merge into it
+    const [confirmState, setConfirmState] = useState<{
+        isOpen: boolean;
+        current: Record<string, any>;
+    }>({
+        isOpen: false,
+        current: {}
+    });
 
     // Initialize form data
     useEffect(() => {
         if (isOpen) {
             if (mode === 'edit' && initialData) {
                 setFormData({ ...initialData });
+                setOriginal(initialData);
             } else if (mode === 'insert') {
                  // If Insert (Copy), we use initialData but we might want to clear auto-inc PK
                  // For now, just pre-fill. User can edit.
                  if (initialData) {
                      const copy = { ...initialData };
-                     if (pkCol) {
+                     if (keyColumns.length > 0) {
                          // Suggest clearing PK for copy? usually handled by users or auto-inc logic. 
                          // We'll leave it populated as "Copy", user typically changes it.
                      }
@@ -51,10 +63,16 @@ export function RowEditorModal({ isOpen, onClose, mode, initialData, pkCol, pkVa
         setLoading(true);
         try {
             if (mode === 'edit') {
-                if (!pkCol) throw new Error("Primary Key required for editing");
+                if (!rowKey) throw new Error("Row key required for editing");
                 // For edit, we only send changed fields? Or all? sending all is safer for "Row Edit".
                 // But efficient web apps might send diff. However, native 'update_row' expects map.
-                await dbApi.updateRow(currentDb, currentTable, formData, pkCol, pkVal);
+                const result = await dbApi.updateRow(currentDb, currentTable, formData, rowKey, original);
+                if (result.conflict?.current) {
+                    setConfirmState({ isOpen: true, current: result.conflict.current });
+                    return;
+                } else if (result.matched === 0) {
+                    throw new Error("Row no longer exists; nothing was updated");
+                }
                 showToast('Row updated successfully', 'success');
             } else {
                 // Insert
@@ -79,6 +97,36 @@ export function RowEditorModal({ isOpen, onClose, mode, initialData, pkCol, pkVa
         }
     };
 
+    const handleOverwrite = async () => {
+        if (!currentDb || !currentTable || !rowKey) return;
+        setConfirmState(prev => ({ ...prev, isOpen: false }));
+        setLoading(true);
+        try {
+            await dbApi.updateRow(currentDb, currentTable, formData, rowKey);
+            showToast('Row updated successfully', 'success');
+            onSuccess();
+            onClose();
+        } catch (e: any) {
+            console.error(e);
+            showToast(e.message || 'Error saving row', 'error');
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const handleMerge = () => {
+        const current = confirmState.current;
+        // Keep only the fields the user actually edited on top of the latest row
+        const merged = { ...current };
+        columns.forEach(c => {
+            if (original && formData[c] !== original[c]) merged[c] = formData[c];
+        });
+        setFormData(merged);
+        setOriginal(current);
+        setConfirmState(prev => ({ ...prev, isOpen: false }));
+        showToast('Merged your edits into the latest values; review and save again', 'info');
+    };
+
     const handleChange = (col: string, val: string) => {
         setFormData(prev => ({ ...prev, [col]: val }));
     };
@@ -101,7 +149,7 @@ export function RowEditorModal({ isOpen, onClose, mode, initialData, pkCol, pkVa
                     {columns.map(col => {
                         const val = formData[col];
                         const isNull = val === null;
-                        const isPk = col === pkCol;
+                        const isPk = keyColumns.includes(col);
 
                         return (
                             <div key={col} className="flex flex-col gap-1 p-3 border border-white/5 rounded bg-black/20">
@@ -146,6 +194,35 @@ export function RowEditorModal({ isOpen, onClose, mode, initialData, pkCol, pkVa
                     </button>
                 </div>
             </div>
+
+            {confirmState.isOpen && (
+                <Modal 
+                    isOpen={confirmState.isOpen} 
+                    onClose={handleMerge}
+                    title="Edit Conflict"
+                    size="sm"
+                >
+                    <div className="p-4 flex flex-col gap-6">
+                        <p className="text-sm text-text-muted">
+                            This row was changed by someone else since it was loaded. Overwrite it with your version, or merge your edits into the latest values and review them?
+                        </p>
+                        <div className="flex justify-end gap-2">
+                            <button 
+                                onClick={handleMerge} 
+                                className="px-4 py-2 rounded text-xs font-bold hover:bg-white/5 transition-colors"
+                            >
+                                Merge
+                            </button>
+                            <button 
+                                onClick={handleOverwrite}
+                                className="bg-primary hover:bg-primary-hover text-white px-4 py-2 rounded text-xs font-bold transition-colors"
+                            >
+                                Overwrite
+                            </button>
+                        </div>
+                    </div>
+                </Modal>
+            )}
         </Modal>
     );
 }
@@ -0,0 +1,98 @@
+import React, { useState } from 'react';
+import { KeyRound, Loader2 } from 'lucide-react';
+import { Modal } from '@/components/ui/Modal';
+import { useVaultStore } from '@/stores/useVaultStore';
+
+/** Master password prompt shown whenever `ensureVaultUnlocked` finds the vault locked. */
+export function VaultUnlockModal() {
+    const { prompt, unlock, cancel } = useVaultStore();
+    const [password, setPassword] = useState('');
+    const [confirmation, setConfirmation] = useState('');
+    const [error, setError] = useState<string | null>(null);
+    const [loading, setLoading] = useState(false);
+
+    if (!prompt) return null;
+    const creating = !prompt.initialized;
+
+    const close = () => {
+        setPassword('');
+        setConfirmation('');
+        setError(null);
+        cancel();
+    };
+
+    const handleSubmit = async (e: React.FormEvent) => {
+        e.preventDefault();
+        if (!password) {
+            setError('Enter the master password.');
+            return;
+        }
+        if (creating && password !== confirmation) {
+            setError('The passwords do not match.');
+            return;
+        }
+        setLoading(true);
+        setError(null);
+        try {
+            await unlock(password);
+            setPassword('');
+            setConfirmation('');
+        } catch (e: any) {
+            setError(e.toString());
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <Modal
+            isOpen={true}
+            onClose={close}
+            title={<span className="flex items-center gap-2"><KeyRound size={18} className="text-primary" /> {creating ? 'Create Credential Vault' : 'Unlock Credential Vault'}</span>}
+            size="sm"
+        >
+            <form onSubmit={handleSubmit} className="p-4 flex flex-col gap-4">
+                <p className="text-sm text-text-muted">
+                    {creating
+                        ? 'Saved passwords are encrypted with a master password. Choose one; it cannot be recovered if lost.'
+                        : 'Enter the master password to use saved passwords. Cancel to continue without them.'}
+                </p>
+                <input
+                    type="password"
+                    autoFocus
+                    value={password}
+                    onChange={e => { setPassword(e.target.value); setError(null); }}
+                    className="w-full bg-canvas border border-border rounded px-3 py-2 text-sm outline-none focus:border-primary transition-colors text-text-main"
+                    placeholder="Master password"
+                />
+                {creating && (
+                    <input
+                        type="password"
+                        value={confirmation}
+                        onChange={e => { setConfirmation(e.target.value); setError(null); }}
+                        className="w-full bg-canvas border border-border rounded px-3 py-2 text-sm outline-none focus:border-primary transition-colors text-text-main"
+                        placeholder="Repeat master password"
+                    />
+                )}
+                {error && <p className="text-xs text-red-400">{error}</p>}
+                <div className="flex justify-end gap-2">
+                    <button
+                        type="button"
+                        onClick={close}
+                        className="px-4 py-2 rounded text-xs font-bold hover:bg-white/5 transition-colors"
+                    >
+                        Cancel
+                    </button>
+                    <button
+                        type="submit"
+                        disabled={loading}
+                        className="bg-primary hover:bg-primary-hover text-white px-4 py-2 rounded text-xs font-bold transition-colors disabled:opacity-50 flex items-center gap-2"
+                    >
+                        {loading && <Loader2 className="animate-spin w-3 h-3" />}
+                        {creating ? 'Create' : 'Unlock'}
+                    </button>
+                </div>
+            </form>
+        </Modal>
+    );
+}
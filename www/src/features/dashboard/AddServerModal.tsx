@@ -2,6 +2,7 @@ import React, { useState } from 'react';
 import { X, Server as ServerIcon, Check, AlertCircle } from 'lucide-react';
 import { dbApi } from '@/api/db';
 import { useAppStore } from '@/stores/useAppStore';
+import { ensureVaultUnlocked } from '@/stores/useVaultStore';
 import { Button } from '@/components/ui/button';
 
 interface AddServerModalProps {
@@ -62,6 +63,12 @@ export function AddServerModal({ onClose, onAdd, editingServer }: AddServerModal
         }
         try {
             const id = editingServer?.id || config.name.toLowerCase().replace(/\s+/g, '-').replace(/[^a-z0-9-]/g, '');
+            // Passwords are stored in the credential vault, which has to be open to take them
+            const hasSecrets = [config.pass, config.ssh_pass, config.ssh_key_passphrase].some(Boolean);
+            if (hasSecrets && !(await ensureVaultUnlocked())) {
+                setError("Unlock the credential vault to save passwords, or clear them to save without.");
+                return;
+            }
             await dbApi.saveServer({ ...config, id });
             onAdd();
             onClose();
@@ -18,7 +18,7 @@ export function Dashboard() {
     // Fetch Saved Servers
     const { data: servers = [], refetch, error, isError } = useQuery({
         queryKey: ['savedServers'],
-        queryFn: dbApi.getSavedServers,
+        queryFn: () => dbApi.getSavedServers(),
         retry: false
     });
 
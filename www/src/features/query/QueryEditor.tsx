@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react';
-import { useMutation, useQuery } from '@tanstack/react-query';
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
 import { Play, Loader2, Clock, Trash2, Database, AlertTriangle, Download, FileJson, Terminal, History as HistoryIcon, Code2, Sparkles, Eraser, Save, Info, Activity } from 'lucide-react';
 import { useAppStore } from '@/stores/useAppStore';
 import { dbApi } from '@/api/db';
@@ -18,7 +18,14 @@ import { EditorView } from '@codemirror/view';
 import { format } from 'sql-formatter';
 
 export function QueryEditor() {
-    const { currentDb, currentTable, queryHistory, addHistory, theme } = useAppStore();
+    const { currentDb, currentTable, currentServer, theme } = useAppStore();
+    const queryClient = useQueryClient();
+
+    // Recorded by execute_query itself
+    const { data: queryHistory = [] } = useQuery({
+        queryKey: ['queryHistory', currentServer?.id],
+        queryFn: () => dbApi.searchQueryHistory({ server_id: currentServer?.id, limit: 50 }),
+    });
     const [mode, setMode] = useState<'editor' | 'explain'>('editor');
     const [sqlCode, setSqlCode] = useState('SELECT * FROM ');
     const [results, setResults] = useState<QueryResult[]>([]);
@@ -76,15 +83,17 @@ export function QueryEditor() {
 
             return dbApi.executeQuery(currentDb, cleanedSql);
         },
-        onSuccess: (data, variables) => {
+        onSuccess: (data) => {
             setResults(Array.isArray(data) ? data : [data]);
             setLastResultIndex(0);
             setError(null);
-            addHistory(variables);
         },
         onError: (err) => {
              setError(err);
              setResults([]);
+        },
+        onSettled: () => {
+            queryClient.invalidateQueries({ queryKey: ['queryHistory'] });
         }
     });
 
@@ -437,9 +446,9 @@ export function QueryEditor() {
                      <HistoryIcon size={12} /> Query History
                  </div>
                  <div className="flex-1 overflow-y-auto">
-                     {queryHistory.map((item, i) => (
+                     {queryHistory.map((item) => (
                          <div 
-                            key={i} 
+                            key={item.id} 
                             className="p-3 border-b border-border hover:bg-hover-bg cursor-pointer group"
                             onClick={() => {
                                 setSqlCode(item.sql);
@@ -450,7 +459,7 @@ export function QueryEditor() {
                                  {item.sql}
                              </div>
                              <div className="text-[9px] opacity-30">
-                                 {item.timestamp.toLocaleTimeString()}
+                                 {new Date(item.timestamp).toLocaleTimeString()}
                              </div>
                          </div>
                      ))}
@@ -9,11 +9,6 @@ interface ServerConfig {
   user: string;
 }
 
-interface QueryHistoryItem {
-  sql: string;
-  timestamp: Date;
-}
-
 export interface AIConfig {
     provider: 'ollama' | 'gemini' | 'openai' | 'disabled';
     apiKey?: string;
@@ -44,7 +39,6 @@ interface AppState {
   currentServer: ServerConfig | null;
   currentDb: string | null;
   currentTable: string | null;
-  queryHistory: QueryHistoryItem[];
 
   // Export Templates
   exportTemplates: ExportTemplate[];
@@ -107,7 +101,6 @@ interface AppState {
   setCurrentServer: (server: ServerConfig | null) => void;
   setCurrentDb: (db: string | null) => void;
   setCurrentTable: (table: string | null) => void;
-  addHistory: (sql: string) => void;
 }
 
 export const useAppStore = create<AppState>((set, get) => ({
@@ -123,7 +116,6 @@ export const useAppStore = create<AppState>((set, get) => ({
       currentServer: null,
       currentDb: null,
       currentTable: null,
-      queryHistory: [],
       customFonts: [],
       customColors: [],
 
@@ -135,7 +127,6 @@ export const useAppStore = create<AppState>((set, get) => ({
           dashboardViewMode: prefs.dashboardViewMode as any,
           tableViewMode: prefs.tableViewMode ?? 'list' as any,
           showSystemDbs: prefs.showSystemDbs,
-          queryHistory: prefs.queryHistory.map((q: any) => ({ ...q, timestamp: new Date(q.timestamp) })),
           // Performance settings
           debugMode: prefs.debugMode ?? false,
           performanceMonitoring: prefs.performanceMonitoring ?? false,
@@ -211,11 +202,6 @@ export const useAppStore = create<AppState>((set, get) => ({
       setCurrentServer: (currentServer) => set({ currentServer }),
       setCurrentDb: (currentDb) => set({ currentDb }),
       setCurrentTable: (currentTable) => set({ currentTable }),
-      addHistory: (sql) => set((state) => {
-          return { 
-              queryHistory: [{ sql, timestamp: new Date() }, ...state.queryHistory].slice(0, 50) 
-          } as Partial<AppState>;
-      }),
       addCustomColor: (color) => set((state) => ({
           customColors: [...state.customColors, color]
       })),
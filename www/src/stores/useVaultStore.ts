@@ -0,0 +1,51 @@
+import { create } from 'zustand';
+import { dbApi } from '@/api/db';
+
+interface VaultPrompt {
+    /** A vault exists, so the existing master password is needed rather than a new one */
+    initialized: boolean;
+    resolve: (unlocked: boolean) => void;
+}
+
+interface VaultState {
+    prompt: VaultPrompt | null;
+    unlock: (masterPassword: string) => Promise<void>;
+    cancel: () => void;
+}
+
+export const useVaultStore = create<VaultState>((set, get) => ({
+    prompt: null,
+
+    unlock: async (masterPassword) => {
+        await dbApi.unlockVault(masterPassword);
+        get().prompt?.resolve(true);
+        set({ prompt: null });
+    },
+
+    cancel: () => {
+        get().prompt?.resolve(false);
+        set({ prompt: null });
+    },
+}));
+
+/**
+ * Resolves to `true` once the credential vault is unlocked, asking for the master password
+ * if it is locked. Resolves to `false` if the user dismisses the prompt.
+ */
+export async function ensureVaultUnlocked(): Promise<boolean> {
+    const { initialized, unlocked } = await dbApi.getVaultStatus();
+    if (unlocked) return true;
+    // A prompt that is already open answers for every caller waiting on it
+    const pending = useVaultStore.getState().prompt;
+    return new Promise(resolve => {
+        useVaultStore.setState({
+            prompt: {
+                initialized,
+                resolve: (result) => {
+                    pending?.resolve(result);
+                    resolve(result);
+                }
+            }
+        });
+    });
+}